@@ -50,7 +50,6 @@ async fn main(_s: Spawner) -> ! {
     esp_hal_embassy::init(timg0.timer0);
 
     // Initialize the LCD
-    Timer::after(Duration::from_millis(50)).await;
     let mut i2c = I2c::new(peripherals.I2C0, esp_hal::i2c::master::Config::default())
         .with_scl(peripherals.GPIO32)
         .with_sda(peripherals.GPIO33)
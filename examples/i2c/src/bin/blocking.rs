@@ -43,7 +43,6 @@ fn main() -> ! {
     let delay = Delay::new();
 
     // Initialize the LCD
-    delay.delay(50.millis());
     let mut i2c = I2c::new(peripherals.I2C0, esp_hal::i2c::master::Config::default())
         .with_scl(peripherals.GPIO32)
         .with_sda(peripherals.GPIO33);
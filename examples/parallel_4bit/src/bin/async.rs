@@ -62,7 +62,6 @@ async fn main(_s: Spawner) -> ! {
     esp_hal_embassy::init(timg0.timer0);
 
     // Initialize the LCD
-    Timer::after(Duration::from_millis(50)).await;
     let mut lcd = create_display(
         Output::new(peripherals.GPIO2, Level::Low),
         Output::new(peripherals.GPIO0, Level::Low),
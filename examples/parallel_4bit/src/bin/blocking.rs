@@ -57,7 +57,6 @@ fn main() -> ! {
     let delay = Delay::new();
 
     // Initialize the LCD
-    delay.delay(50.millis());
     let mut lcd = create_display(
         Output::new(peripherals.GPIO2, Level::Low),
         Output::new(peripherals.GPIO0, Level::Low),
@@ -69,7 +69,6 @@ async fn main(_s: Spawner) -> ! {
     esp_hal_embassy::init(timg0.timer0);
 
     // Initialize the LCD
-    Timer::after(Duration::from_millis(50)).await;
     let mut lcd = create_display(
         Output::new(peripherals.GPIO22, Level::Low), // D0
         Output::new(peripherals.GPIO21, Level::Low), // D1
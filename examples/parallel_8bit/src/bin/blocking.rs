@@ -89,7 +89,6 @@ fn main() -> ! {
     esp_println::logger::init_logger_from_env();
 
     // Initialize the LCD
-    delay.delay(50.millis());
     let mut lcd = create_display(
         Output::new(peripherals.GPIO22, Level::Low), // D0
         Output::new(peripherals.GPIO21, Level::Low), // D1
@@ -0,0 +1,111 @@
+use crate::controllers::Controller;
+use crate::display::{character_as_byte, ddram_address};
+use crate::{Command, Lines, Shift, ShiftDirection};
+
+// Enough for two lines of a 20x4 display including the positioning
+pub const BATCH_CAPACITY: usize = 48;
+
+#[derive(Debug)]
+pub enum BatchError<E> {
+    // More commands than fit into the batch, nothing has been sent
+    Overflow,
+    InterfaceError(E),
+}
+
+/// Commands collected by `Display::batch`, which are handed to the interface at once.
+///
+/// Once the capacity is exceeded, further commands are dropped and the batch is not sent at all.
+#[derive(Debug)]
+pub struct Batch<'a, C> {
+    controller: &'a C,
+    lines: Lines,
+    commands: [Command; BATCH_CAPACITY],
+    len: usize,
+    overflow: bool,
+}
+
+impl<'a, C: Controller> Batch<'a, C> {
+    #[inline]
+    pub(crate) fn new(controller: &'a C, lines: Lines) -> Self {
+        Self {
+            controller,
+            lines,
+            commands: [Command::Delay(0); BATCH_CAPACITY],
+            len: 0,
+            overflow: false,
+        }
+    }
+
+    pub(crate) fn commands<E>(&self) -> Result<&[Command], BatchError<E>> {
+        match self.overflow {
+            true => Err(BatchError::Overflow),
+            false => Ok(&self.commands[..self.len]),
+        }
+    }
+
+    pub fn command(&mut self, command: Command) -> &mut Self {
+        match self.commands.get_mut(self.len) {
+            Some(slot) => {
+                *slot = command;
+                self.len += 1;
+            }
+            None => self.overflow = true,
+        }
+        self
+    }
+
+    #[inline]
+    pub fn clear(&mut self) -> &mut Self {
+        self.command(Command::Clear)
+    }
+
+    #[inline]
+    pub fn home(&mut self) -> &mut Self {
+        self.command(Command::Home)
+    }
+
+    #[inline]
+    pub fn shift(&mut self, shift: Shift, shift_direction: ShiftDirection) -> &mut Self {
+        self.command(Command::Shift(shift, shift_direction))
+    }
+
+    #[inline]
+    pub fn pos(&mut self, line: Lines, position: u8) -> &mut Self {
+        let address = ddram_address(self.controller, self.lines, line, position);
+        self.command(Command::SetDdramAddress(address))
+    }
+
+    #[inline]
+    pub fn write_byte(&mut self, data: u8) -> &mut Self {
+        self.command(Command::Data(data))
+    }
+
+    pub fn write_bytes(&mut self, data: &[u8]) -> &mut Self {
+        for b in data {
+            self.write_byte(*b);
+        }
+        self
+    }
+
+    #[inline]
+    pub fn write_character(&mut self, c: char) -> &mut Self {
+        self.write_byte(character_as_byte(c))
+    }
+
+    pub fn write_str(&mut self, s: &str) -> &mut Self {
+        for c in s.chars() {
+            self.write_character(c);
+        }
+        self
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
@@ -0,0 +1,67 @@
+/// Monotonic time source of a `Display`.
+///
+/// With a clock, the display records when the controller is ready again and waits only for the
+/// remaining time before the next transfer, so time spent elsewhere counts towards the waits.
+pub trait Clock {
+    /// Whether the clock measures time. Only `NoClock` stands still.
+    const MEASURES_TIME: bool = true;
+
+    /// Current time in µs. Only differences are used, so it may wrap around.
+    fn now_us(&mut self) -> u32;
+}
+
+/// Clock of displays without a time source. Time stands still, so every wait is done in full.
+#[derive(Default, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct NoClock;
+
+impl Clock for NoClock {
+    const MEASURES_TIME: bool = false;
+
+    #[inline]
+    fn now_us(&mut self) -> u32 {
+        0
+    }
+}
+
+// Point in time at which the controller accepts the next transfer
+#[derive(Debug)]
+pub(crate) struct Ready<K> {
+    clock: K,
+    at: Option<u32>,
+}
+
+impl<K: Clock> Ready<K> {
+    #[inline]
+    pub(crate) fn new(clock: K) -> Self {
+        Self { clock, at: None }
+    }
+
+    #[inline]
+    pub(crate) fn busy_for(&mut self, us: u32) {
+        self.at = match us {
+            0 => None,
+            _ => Some(self.clock.now_us().wrapping_add(us)),
+        };
+    }
+
+    #[cfg(feature = "nb")]
+    #[inline]
+    pub(crate) fn is_ready(&mut self) -> bool {
+        match self.at {
+            Some(at) if (at.wrapping_sub(self.clock.now_us()) as i32) > 0 => false,
+            _ => {
+                self.at = None;
+                true
+            }
+        }
+    }
+
+    // Time left until the controller is ready, which is considered passed afterward
+    #[inline]
+    pub(crate) fn remaining_us(&mut self) -> u32 {
+        match self.at.take() {
+            Some(at) => (at.wrapping_sub(self.clock.now_us()) as i32).max(0) as u32,
+            None => 0,
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{Shift, ShiftDirection, Timing};
+
+const CLEAR: u8 = 0b0000_0001;
+const HOME: u8 = 0b0000_0010;
+const ENTRY_MODE_SET: u8 = 0b0000_0100;
+const DISPLAY_CONTROL: u8 = 0b0000_1000;
+const SHIFT: u8 = 0b0001_0000;
+const FUNCTION_SET: u8 = 0b0010_0000;
+const SET_CGRAM_ADDRESS: u8 = 0b0100_0000;
+const SET_DDRAM_ADDRESS: u8 = 0b1000_0000;
+
+/// A single transfer to the controller.
+///
+/// The instructions with flags take the flag bits only, the instruction bit is added by the
+/// encoding. Controller specific instructions are sent with `Instruction`, which covers the
+/// extended instruction sets as well.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Command {
+    Clear,
+    Home,
+    EntryModeSet(u8),
+    DisplayControl(u8),
+    Shift(Shift, ShiftDirection),
+    FunctionSet(u8),
+    SetCgramAddress(u8),
+    SetDdramAddress(u8),
+    /// Raw instruction byte
+    Instruction(u8),
+    /// Byte written to the CGRAM or DDRAM
+    Data(u8),
+    /// Time to wait in µs
+    Delay(u32),
+}
+
+impl Command {
+    /// Byte on the bus and whether it is sent in command mode. Delays send nothing.
+    pub fn encode(&self) -> Option<(u8, bool)> {
+        match *self {
+            Command::Clear => Some((CLEAR, true)),
+            Command::Home => Some((HOME, true)),
+            Command::EntryModeSet(flags) => Some((ENTRY_MODE_SET | flags, true)),
+            Command::DisplayControl(flags) => Some((DISPLAY_CONTROL | flags, true)),
+            Command::Shift(shift, direction) => {
+                Some((SHIFT | (shift as u8 + direction as u8) << 2, true))
+            }
+            Command::FunctionSet(flags) => Some((FUNCTION_SET | flags, true)),
+            Command::SetCgramAddress(address) => {
+                Some((SET_CGRAM_ADDRESS | (address & 0b0011_1111), true))
+            }
+            Command::SetDdramAddress(address) => {
+                Some((SET_DDRAM_ADDRESS | (address & 0b0111_1111), true))
+            }
+            Command::Instruction(instruction) => Some((instruction, true)),
+            Command::Data(data) => Some((data, false)),
+            Command::Delay(_) => None,
+        }
+    }
+
+    /// Time the controller needs to execute the command, before the next one can be sent
+    pub fn delay_us(&self, timing: &Timing) -> u32 {
+        match *self {
+            Command::Clear | Command::Instruction(CLEAR) => timing.clear_us,
+            Command::Home => timing.home_us,
+            Command::Instruction(instruction) if instruction & !1 == HOME => timing.home_us,
+            Command::Delay(us) => us,
+            _ => timing.execution_us,
+        }
+    }
+}
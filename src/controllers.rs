@@ -0,0 +1,158 @@
+use crate::{Command, Lines, Timing};
+
+#[repr(u8)]
+#[derive(Default, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum DoubleHeight {
+    #[default]
+    Top = 0b0000,
+    Middle = 0b0100,
+    Bottom = 0b1000,
+    TopAndBottom = 0b1100,
+}
+
+#[repr(u8)]
+#[derive(Default, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Rotation {
+    #[default]
+    _0 = 0b0000_0110,
+    _180 = 0b0000_0101,
+}
+
+// Function set bits
+const FONT_5X10: u8 = 0b0000_0100;
+const TWO_LINES: u8 = 0b0000_1000;
+
+// Sets the line bit of the function set. Three and four lines are driven in the two line mode,
+// and the 5x10 font is only available on a single line.
+pub(crate) fn line_function_set(function_set: u8, lines: Lines) -> u8 {
+    match lines {
+        Lines::_1 => function_set,
+        _ if function_set & FONT_5X10 != 0 => function_set,
+        Lines::_2 | Lines::_3 | Lines::_4 => function_set | TWO_LINES,
+    }
+}
+
+// Sets the line bit of the function set for the controllers which assign bit 2 a meaning of
+// their own. The font bit is dropped, and unlike the HD44780 the line bit does not depend on it.
+pub(crate) fn extended_line_function_set(function_set: u8, lines: Lines) -> u8 {
+    line_function_set(function_set & !FONT_5X10, lines)
+}
+
+pub trait Controller {
+    // Instructions sent after the function set during the initialization
+    fn init_sequence(&self, function_set: u8, lines: Lines) -> impl Iterator<Item = Command>;
+
+    // DDRAM address of the first character of a line. The third and fourth line continue the
+    // first and second one, at the offsets of modules with 20 columns.
+    #[inline]
+    fn row_offset(&self, _lines: Lines, line: Lines) -> u8 {
+        match line {
+            Lines::_1 => 0x00,
+            Lines::_2 => 0x40,
+            Lines::_3 => 0x14,
+            Lines::_4 => 0x54,
+        }
+    }
+
+    // Function set for the number of lines, to which the controller may add further bits
+    #[inline]
+    fn function_set(&self, function_set: u8, lines: Lines) -> u8 {
+        line_function_set(function_set, lines)
+    }
+
+    // Allows the controller to extend the timing of the interface
+    #[inline]
+    fn timing(&self, timing: Timing) -> Timing {
+        timing
+    }
+}
+
+// Row layout of the controllers with an extended function set. In the three and four line modes,
+// the rows are located at a stride of 0x20.
+pub(crate) fn extended_row_offset(lines: Lines, line: Lines) -> u8 {
+    match (lines, line) {
+        (_, Lines::_1) => 0x00,
+        (Lines::_3 | Lines::_4, Lines::_2) => 0x20,
+        (_, Lines::_2) => 0x40,
+        (_, Lines::_3) => 0x40,
+        (_, Lines::_4) => 0x60,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Hd44780 {
+    columns: u8,
+}
+
+impl Default for Hd44780 {
+    fn default() -> Self {
+        Self { columns: 20 }
+    }
+}
+
+impl Hd44780 {
+    // Characters per line, which determines where the third and fourth line start. A line holds
+    // up to 40 characters.
+    #[inline]
+    pub fn with_columns(mut self, columns: u8) -> Self {
+        self.columns = columns.min(40);
+        self
+    }
+}
+
+impl Controller for Hd44780 {
+    #[inline]
+    fn init_sequence(&self, _function_set: u8, _lines: Lines) -> impl Iterator<Item = Command> {
+        core::iter::empty()
+    }
+
+    #[inline]
+    fn row_offset(&self, _lines: Lines, line: Lines) -> u8 {
+        match line {
+            Lines::_1 => 0x00,
+            Lines::_2 => 0x40,
+            Lines::_3 => self.columns,
+            Lines::_4 => 0x40 + self.columns,
+        }
+    }
+}
+
+// Re-exports
+mod ks0073;
+mod ssd1803a;
+mod st7032;
+mod us2066;
+mod ws0010;
+
+pub use ks0073::Ks0073;
+pub use ssd1803a::Ssd1803a;
+pub use st7032::St7032;
+pub use us2066::Us2066;
+pub use ws0010::Ws0010;
+#[cfg(feature = "graphics")]
+pub(crate) use ws0010::CHARACTER_MODE;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Font;
+
+    fn four_line_function_set(controller: impl Controller) -> u8 {
+        controller.function_set(Font::_5x10 as u8, Lines::_4)
+    }
+
+    #[test]
+    fn ks0073_keeps_two_lines_with_the_5x10_font() {
+        assert_eq!(four_line_function_set(Ks0073::default()), TWO_LINES);
+    }
+
+    #[test]
+    fn ssd1803a_keeps_two_lines_with_the_5x10_font() {
+        assert_eq!(four_line_function_set(Ssd1803a::default()), TWO_LINES);
+    }
+
+    #[test]
+    fn us2066_keeps_two_lines_with_the_5x10_font() {
+        assert_eq!(four_line_function_set(Us2066::default()), TWO_LINES);
+    }
+}
@@ -0,0 +1,53 @@
+use crate::controllers::{extended_line_function_set, extended_row_offset, Controller};
+use crate::{Command, Lines, Timing};
+
+// Selects the extended instruction set in the function set. The KS0073 uses the bit of the
+// HD44780 font selection, so the 5x10 font is not available.
+const EXTENDED_REGISTER: u8 = 0b0000_0100;
+
+// Extended instructions (RE = 1)
+const EXTENDED_FUNCTION_SET: u8 = 0b0000_1000;
+const FOUR_LINES: u8 = 0b0000_0001;
+
+// The clear display and return home instructions take up to 2.16ms at the lowest oscillator
+// frequency
+const CLEAR_HOME_US: u32 = 2200;
+
+#[derive(Default, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Ks0073 {}
+
+impl Controller for Ks0073 {
+    fn init_sequence(&self, function_set: u8, lines: Lines) -> impl Iterator<Item = Command> {
+        [
+            Command::Instruction(function_set | EXTENDED_REGISTER),
+            Command::Instruction(
+                EXTENDED_FUNCTION_SET
+                    | match lines {
+                        Lines::_3 | Lines::_4 => FOUR_LINES,
+                        _ => 0,
+                    },
+            ),
+            Command::Instruction(function_set),
+        ]
+        .into_iter()
+    }
+
+    #[inline]
+    fn row_offset(&self, lines: Lines, line: Lines) -> u8 {
+        extended_row_offset(lines, line)
+    }
+
+    #[inline]
+    fn function_set(&self, function_set: u8, lines: Lines) -> u8 {
+        extended_line_function_set(function_set, lines)
+    }
+
+    #[inline]
+    fn timing(&self, timing: Timing) -> Timing {
+        Timing {
+            clear_us: timing.clear_us.max(CLEAR_HOME_US),
+            home_us: timing.home_us.max(CLEAR_HOME_US),
+            ..timing
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::controllers::{
+    extended_line_function_set, extended_row_offset, Controller, DoubleHeight, Rotation,
+};
+use crate::interfaces::{AsyncInterface, BlockingInterface, BusWidth};
+use crate::{Async, Blocking, Clock, Command, Display, Lines};
+
+// Function set bits
+const INSTRUCTION_TABLE: u8 = 0b0000_0001;
+const EXTENDED_REGISTER: u8 = 0b0000_0010;
+const DOUBLE_HEIGHT: u8 = 0b0000_0100;
+
+// Extended instructions (RE = 1)
+const EXTENDED_FUNCTION_SET: u8 = 0b0000_1000;
+const FOUR_LINES: u8 = 0b0000_0001;
+const DOUBLE_HEIGHT_BIAS: u8 = 0b0001_0010; // BS1 = 1
+
+// Extended instructions (IS = 1)
+const OSCILLATOR: u8 = 0b0001_1011; // BS0 = 1 (1/6 bias), 540 kHz
+const POWER_ICON_CONTRAST: u8 = 0b0101_0000;
+const BOOSTER: u8 = 0b0000_0100;
+const FOLLOWER: u8 = 0b0110_1000;
+const CONTRAST: u8 = 0b0111_0000;
+
+// Time for the follower circuit to stabilize the LCD voltage
+const FOLLOWER_STABILIZATION_US: u32 = 200_000;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Ssd1803a {
+    contrast: u8,
+    booster: bool,
+    follower_ratio: u8,
+    rotation: Rotation,
+    double_height: Option<DoubleHeight>,
+}
+
+impl Default for Ssd1803a {
+    fn default() -> Self {
+        Self {
+            contrast: 0b0010_0010,
+            booster: true,
+            follower_ratio: 6,
+            rotation: Rotation::default(),
+            double_height: None,
+        }
+    }
+}
+
+impl Ssd1803a {
+    // Contrast in the range of 0 .. 63
+    #[inline]
+    pub fn with_contrast(mut self, contrast: u8) -> Self {
+        self.contrast = contrast & 0b0011_1111;
+        self
+    }
+
+    // The booster is required for supply voltages below 4.5V
+    #[inline]
+    pub fn with_booster(mut self, enable: bool) -> Self {
+        self.booster = enable;
+        self
+    }
+
+    // Amplifier ratio of the follower circuit in the range of 0 .. 7
+    #[inline]
+    pub fn with_follower_ratio(mut self, ratio: u8) -> Self {
+        self.follower_ratio = ratio & 0b0000_0111;
+        self
+    }
+
+    #[inline]
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    #[inline]
+    pub fn with_double_height(mut self, double_height: Option<DoubleHeight>) -> Self {
+        self.double_height = double_height;
+        self
+    }
+
+    #[inline]
+    pub fn contrast(&self) -> u8 {
+        self.contrast
+    }
+
+    #[inline]
+    fn power_icon_contrast(&self) -> u8 {
+        POWER_ICON_CONTRAST
+            | match self.booster {
+                true => BOOSTER,
+                false => 0,
+            }
+            | self.contrast >> 4
+    }
+
+    #[inline]
+    fn contrast_low(&self) -> u8 {
+        CONTRAST | (self.contrast & 0b0000_1111)
+    }
+
+    #[inline]
+    fn double_height_bias(&self) -> u8 {
+        DOUBLE_HEIGHT_BIAS | self.double_height.unwrap_or_default() as u8
+    }
+
+    // Function set with the extended register selected. Bit 2 is the blink enable bit with
+    // RE = 1 and must not carry the double height flag.
+    #[inline]
+    fn extended(function_set: u8) -> u8 {
+        (function_set & !DOUBLE_HEIGHT) | EXTENDED_REGISTER
+    }
+
+    fn contrast_sequence(&self, function_set: u8) -> [Command; 4] {
+        [
+            Command::Instruction(function_set | INSTRUCTION_TABLE),
+            Command::Instruction(self.power_icon_contrast()),
+            Command::Instruction(self.contrast_low()),
+            Command::Instruction(function_set),
+        ]
+    }
+
+    fn rotation_sequence(&self, function_set: u8) -> [Command; 3] {
+        [
+            Command::Instruction(Self::extended(function_set)),
+            Command::Instruction(self.rotation as u8),
+            Command::Instruction(function_set),
+        ]
+    }
+
+    fn double_height_sequence(&self, function_set: u8) -> [Command; 3] {
+        [
+            Command::Instruction(Self::extended(function_set)),
+            Command::Instruction(self.double_height_bias()),
+            Command::Instruction(function_set),
+        ]
+    }
+}
+
+impl Controller for Ssd1803a {
+    fn init_sequence(&self, function_set: u8, lines: Lines) -> impl Iterator<Item = Command> {
+        [
+            // Lines, view direction and bias
+            Command::Instruction(Self::extended(function_set)),
+            Command::Instruction(
+                EXTENDED_FUNCTION_SET
+                    | match lines {
+                        Lines::_3 | Lines::_4 => FOUR_LINES,
+                        _ => 0,
+                    },
+            ),
+            Command::Instruction(self.rotation as u8),
+            Command::Instruction(self.double_height_bias()),
+            // Oscillator, voltage generation and contrast
+            Command::Instruction(function_set | INSTRUCTION_TABLE),
+            Command::Instruction(OSCILLATOR),
+            Command::Instruction(FOLLOWER | self.follower_ratio),
+            Command::Delay(FOLLOWER_STABILIZATION_US),
+            Command::Instruction(self.power_icon_contrast()),
+            Command::Instruction(self.contrast_low()),
+            Command::Instruction(function_set),
+        ]
+        .into_iter()
+    }
+
+    #[inline]
+    fn row_offset(&self, lines: Lines, line: Lines) -> u8 {
+        extended_row_offset(lines, line)
+    }
+
+    #[inline]
+    fn function_set(&self, function_set: u8, lines: Lines) -> u8 {
+        let function_set = extended_line_function_set(function_set, lines);
+        match self.double_height {
+            Some(_) => function_set | DOUBLE_HEIGHT,
+            None => function_set & !DOUBLE_HEIGHT,
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// BLOCKING INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<I, W, K> Display<I, W, Blocking, Ssd1803a, K>
+where
+    W: BusWidth,
+    I: BlockingInterface<W>,
+    K: Clock,
+{
+    pub fn set_contrast(&mut self, contrast: u8) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting contrast to {}", contrast);
+        self.controller_mut().contrast = contrast & 0b0011_1111;
+        self.send_all(self.controller().contrast_sequence(self.function_set()))
+    }
+
+    pub fn set_rotation(&mut self, rotation: Rotation) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting rotation to {:?}", rotation);
+        self.controller_mut().rotation = rotation;
+        self.send_all(self.controller().rotation_sequence(self.function_set()))
+    }
+
+    pub fn set_double_height(
+        &mut self,
+        double_height: Option<DoubleHeight>,
+    ) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting double height to {:?}", double_height);
+        self.controller_mut().double_height = double_height;
+        self.send_all(
+            self.controller()
+                .double_height_sequence(self.function_set()),
+        )
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// ASYNC INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<I, W, K> Display<I, W, Async, Ssd1803a, K>
+where
+    W: BusWidth,
+    I: AsyncInterface<W>,
+    K: Clock,
+{
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting contrast to {}", contrast);
+        self.controller_mut().contrast = contrast & 0b0011_1111;
+        self.send_all(self.controller().contrast_sequence(self.function_set()))
+            .await
+    }
+
+    pub async fn set_rotation(&mut self, rotation: Rotation) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting rotation to {:?}", rotation);
+        self.controller_mut().rotation = rotation;
+        self.send_all(self.controller().rotation_sequence(self.function_set()))
+            .await
+    }
+
+    pub async fn set_double_height(
+        &mut self,
+        double_height: Option<DoubleHeight>,
+    ) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting double height to {:?}", double_height);
+        self.controller_mut().double_height = double_height;
+        self.send_all(
+            self.controller()
+                .double_height_sequence(self.function_set()),
+        )
+        .await
+    }
+}
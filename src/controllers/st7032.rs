@@ -0,0 +1,219 @@
+use crate::controllers::Controller;
+use crate::interfaces::{AsyncInterface, BlockingInterface, BusWidth};
+use crate::{Async, Blocking, Clock, Command, Display, Lines};
+
+// Selects the extended instruction table in the function set
+const INSTRUCTION_TABLE: u8 = 0b0000_0001;
+
+// Extended instructions (IS = 1)
+const OSCILLATOR: u8 = 0b0001_0100; // 1/5 bias, 183 Hz frame frequency
+const ICON_ADDRESS: u8 = 0b0100_0000;
+const POWER_ICON_CONTRAST: u8 = 0b0101_0000;
+const ICON: u8 = 0b0000_1000;
+const BOOSTER: u8 = 0b0000_0100;
+const FOLLOWER: u8 = 0b0110_1000;
+const CONTRAST: u8 = 0b0111_0000;
+
+// Time for the follower circuit to stabilize the LCD voltage
+const FOLLOWER_STABILIZATION_US: u32 = 200_000;
+
+const ICON_ADDRESSES: usize = 16;
+const ICON_SEGMENTS: u8 = 5;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct St7032 {
+    contrast: u8,
+    booster: bool,
+    follower_ratio: u8,
+    icons: bool,
+    icon_ram: [u8; ICON_ADDRESSES],
+}
+
+impl Default for St7032 {
+    fn default() -> Self {
+        Self {
+            contrast: 32,
+            booster: true,
+            follower_ratio: 4,
+            icons: false,
+            icon_ram: [0; ICON_ADDRESSES],
+        }
+    }
+}
+
+impl St7032 {
+    // Contrast in the range of 0 .. 63
+    #[inline]
+    pub fn with_contrast(mut self, contrast: u8) -> Self {
+        self.contrast = contrast & 0b0011_1111;
+        self
+    }
+
+    // The booster is required for supply voltages below 4.5V
+    #[inline]
+    pub fn with_booster(mut self, enable: bool) -> Self {
+        self.booster = enable;
+        self
+    }
+
+    // Amplifier ratio of the follower circuit in the range of 0 .. 7
+    #[inline]
+    pub fn with_follower_ratio(mut self, ratio: u8) -> Self {
+        self.follower_ratio = ratio & 0b0000_0111;
+        self
+    }
+
+    #[inline]
+    pub fn contrast(&self) -> u8 {
+        self.contrast
+    }
+
+    #[inline]
+    fn power_icon_contrast(&self) -> u8 {
+        POWER_ICON_CONTRAST
+            | match self.icons {
+                true => ICON,
+                false => 0,
+            }
+            | match self.booster {
+                true => BOOSTER,
+                false => 0,
+            }
+            | self.contrast >> 4
+    }
+
+    #[inline]
+    fn contrast_low(&self) -> u8 {
+        CONTRAST | (self.contrast & 0b0000_1111)
+    }
+
+    fn contrast_sequence(&self, function_set: u8) -> [Command; 4] {
+        [
+            Command::Instruction(function_set | INSTRUCTION_TABLE),
+            Command::Instruction(self.contrast_low()),
+            Command::Instruction(self.power_icon_contrast()),
+            Command::Instruction(function_set),
+        ]
+    }
+
+    fn icon_control_sequence(&self, function_set: u8) -> [Command; 3] {
+        [
+            Command::Instruction(function_set | INSTRUCTION_TABLE),
+            Command::Instruction(self.power_icon_contrast()),
+            Command::Instruction(function_set),
+        ]
+    }
+
+    fn icon_sequence(&self, function_set: u8, address: u8) -> [Command; 4] {
+        let address = address % ICON_ADDRESSES as u8;
+        [
+            Command::Instruction(function_set | INSTRUCTION_TABLE),
+            Command::Instruction(ICON_ADDRESS | address),
+            Command::Data(self.icon_ram[address as usize]),
+            Command::Instruction(function_set),
+        ]
+    }
+
+    fn set_icon_segment(&mut self, address: u8, segment: u8, on: bool) {
+        let data = &mut self.icon_ram[address as usize % ICON_ADDRESSES];
+        match on {
+            true => *data |= 1 << (ICON_SEGMENTS - 1 - segment % ICON_SEGMENTS),
+            false => *data &= !(1 << (ICON_SEGMENTS - 1 - segment % ICON_SEGMENTS)),
+        }
+    }
+}
+
+impl Controller for St7032 {
+    fn init_sequence(&self, function_set: u8, _lines: Lines) -> impl Iterator<Item = Command> {
+        [
+            Command::Instruction(function_set | INSTRUCTION_TABLE),
+            Command::Instruction(OSCILLATOR),
+            Command::Instruction(self.contrast_low()),
+            Command::Instruction(self.power_icon_contrast()),
+            Command::Instruction(FOLLOWER | self.follower_ratio),
+            Command::Delay(FOLLOWER_STABILIZATION_US),
+            Command::Instruction(function_set),
+        ]
+        .into_iter()
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// BLOCKING INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<I, W, K> Display<I, W, Blocking, St7032, K>
+where
+    W: BusWidth,
+    I: BlockingInterface<W>,
+    K: Clock,
+{
+    pub fn set_contrast(&mut self, contrast: u8) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting contrast to {}", contrast);
+        self.controller_mut().contrast = contrast & 0b0011_1111;
+        self.send_all(self.controller().contrast_sequence(self.function_set()))
+    }
+
+    pub fn enable_icons(&mut self, enable: bool) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Enable icons: {}", enable);
+        self.controller_mut().icons = enable;
+        self.send_all(self.controller().icon_control_sequence(self.function_set()))
+    }
+
+    // Switches a single segment of the icon RAM. Segment 0 is the leftmost of the five segments
+    // at the given address. The cursor is moved back to its previous position afterward.
+    pub fn set_icon_segment(&mut self, address: u8, segment: u8, on: bool) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Switching icon segment {}/{}: {}", address, segment, on);
+        self.controller_mut().set_icon_segment(address, segment, on);
+        self.send_detached(
+            self.controller()
+                .icon_sequence(self.function_set(), address),
+        )
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// ASYNC INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<I, W, K> Display<I, W, Async, St7032, K>
+where
+    W: BusWidth,
+    I: AsyncInterface<W>,
+    K: Clock,
+{
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting contrast to {}", contrast);
+        self.controller_mut().contrast = contrast & 0b0011_1111;
+        self.send_all(self.controller().contrast_sequence(self.function_set()))
+            .await
+    }
+
+    pub async fn enable_icons(&mut self, enable: bool) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Enable icons: {}", enable);
+        self.controller_mut().icons = enable;
+        self.send_all(self.controller().icon_control_sequence(self.function_set()))
+            .await
+    }
+
+    // Switches a single segment of the icon RAM. Segment 0 is the leftmost of the five segments
+    // at the given address. The cursor is moved back to its previous position afterward.
+    pub async fn set_icon_segment(
+        &mut self,
+        address: u8,
+        segment: u8,
+        on: bool,
+    ) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Switching icon segment {}/{}: {}", address, segment, on);
+        self.controller_mut().set_icon_segment(address, segment, on);
+        self.send_detached(
+            self.controller()
+                .icon_sequence(self.function_set(), address),
+        )
+        .await
+    }
+}
@@ -0,0 +1,330 @@
+use crate::controllers::{
+    extended_line_function_set, extended_row_offset, Controller, DoubleHeight,
+};
+use crate::interfaces::{AsyncInterface, BlockingInterface, BusWidth};
+use crate::{Async, Blocking, Clock, Command, Display, Lines};
+
+// Function set bits
+const EXTENDED_REGISTER: u8 = 0b0000_0010;
+const DOUBLE_HEIGHT: u8 = 0b0000_0100;
+
+// Extended instructions (RE = 1)
+const FUNCTION_SELECTION_A: u8 = 0b0111_0001;
+const FUNCTION_SELECTION_B: u8 = 0b0111_0010;
+const OLED_COMMANDS_DISABLED: u8 = 0b0111_1000;
+const OLED_COMMANDS_ENABLED: u8 = 0b0111_1001;
+const EXTENDED_FUNCTION_SET: u8 = 0b0000_1000;
+const FOUR_LINES: u8 = 0b0000_0001;
+const SCAN_DIRECTION: u8 = 0b0000_0110; // COM0 -> COM31, SEG0 -> SEG99
+const DOUBLE_HEIGHT_LINES: u8 = 0b0001_0000;
+const DISPLAY_OFF: u8 = 0b0000_1000;
+
+// Internal regulator for the 5V I/O mode
+const REGULATOR_ENABLED: u8 = 0x5C;
+const REGULATOR_DISABLED: u8 = 0x00;
+// ROM A with 8 CGRAM characters
+const CHARACTER_ROM: u8 = 0x00;
+
+// OLED commands (RE = 1, SD = 1), each followed by a parameter byte
+const CLOCK_DIVIDER: u8 = 0xD5;
+const SEGMENT_CONFIGURATION: u8 = 0xDA;
+const FUNCTION_SELECTION_C: u8 = 0xDC;
+const CONTRAST: u8 = 0x81;
+const PHASE_LENGTH: u8 = 0xD9;
+const VCOMH_DESELECT_LEVEL: u8 = 0xDB;
+const FADE_OUT_AND_BLINKING: u8 = 0x23;
+
+const DEFAULT_CLOCK: u8 = 0x70;
+const ALTERNATIVE_SEGMENTS: u8 = 0b0001_0000;
+const SEGMENT_REMAP: u8 = 0b0010_0000;
+const DEFAULT_PHASE_LENGTH: u8 = 0xF1;
+const DEFAULT_VCOMH: u8 = 0x40;
+
+const FADE_DISABLED: u8 = 0b0000_0000;
+const FADE_OUT: u8 = 0b0010_0000;
+const BLINKING: u8 = 0b0011_0000;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Us2066 {
+    contrast: u8,
+    internal_regulator: bool,
+    segment_remap: bool,
+    double_height: Option<DoubleHeight>,
+}
+
+impl Default for Us2066 {
+    fn default() -> Self {
+        Self {
+            contrast: 0x7F,
+            internal_regulator: true,
+            segment_remap: false,
+            double_height: None,
+        }
+    }
+}
+
+impl Us2066 {
+    #[inline]
+    pub fn with_contrast(mut self, contrast: u8) -> Self {
+        self.contrast = contrast;
+        self
+    }
+
+    // The internal regulator is required when running the I/O at 5V
+    #[inline]
+    pub fn with_internal_regulator(mut self, enable: bool) -> Self {
+        self.internal_regulator = enable;
+        self
+    }
+
+    // Mirrors the segments for modules with a flipped glass
+    #[inline]
+    pub fn with_segment_remap(mut self, enable: bool) -> Self {
+        self.segment_remap = enable;
+        self
+    }
+
+    #[inline]
+    pub fn with_double_height(mut self, double_height: Option<DoubleHeight>) -> Self {
+        self.double_height = double_height;
+        self
+    }
+
+    #[inline]
+    pub fn contrast(&self) -> u8 {
+        self.contrast
+    }
+
+    // Function set with the extended register selected. Bit 2 is the CGRAM blink enable bit
+    // with RE = 1 and must not carry the double height flag.
+    #[inline]
+    fn extended(function_set: u8) -> u8 {
+        (function_set & !DOUBLE_HEIGHT) | EXTENDED_REGISTER
+    }
+
+    fn oled_command(function_set: u8, command: u8, parameter: u8) -> [Command; 6] {
+        [
+            Command::Instruction(Self::extended(function_set)),
+            Command::Instruction(OLED_COMMANDS_ENABLED),
+            Command::Instruction(command),
+            Command::Instruction(parameter),
+            Command::Instruction(OLED_COMMANDS_DISABLED),
+            Command::Instruction(function_set),
+        ]
+    }
+
+    fn double_height_sequence(&self, function_set: u8) -> [Command; 3] {
+        [
+            Command::Instruction(Self::extended(function_set)),
+            Command::Instruction(
+                DOUBLE_HEIGHT_LINES | self.double_height.unwrap_or_default() as u8,
+            ),
+            Command::Instruction(function_set),
+        ]
+    }
+}
+
+impl Controller for Us2066 {
+    fn init_sequence(&self, function_set: u8, lines: Lines) -> impl Iterator<Item = Command> {
+        let extended = Self::extended(function_set);
+        [
+            // Configure the internal regulator
+            Command::Instruction(extended),
+            Command::Instruction(FUNCTION_SELECTION_A),
+            Command::Data(match self.internal_regulator {
+                true => REGULATOR_ENABLED,
+                false => REGULATOR_DISABLED,
+            }),
+            Command::Instruction(function_set),
+            Command::Instruction(DISPLAY_OFF),
+            // Clock, lines, scan direction and character ROM
+            Command::Instruction(extended),
+            Command::Instruction(OLED_COMMANDS_ENABLED),
+            Command::Instruction(CLOCK_DIVIDER),
+            Command::Instruction(DEFAULT_CLOCK),
+            Command::Instruction(OLED_COMMANDS_DISABLED),
+            Command::Instruction(
+                EXTENDED_FUNCTION_SET
+                    | match lines {
+                        Lines::_3 | Lines::_4 => FOUR_LINES,
+                        _ => 0,
+                    },
+            ),
+            Command::Instruction(SCAN_DIRECTION),
+            Command::Instruction(FUNCTION_SELECTION_B),
+            Command::Data(CHARACTER_ROM),
+            // Segments, voltages and contrast
+            Command::Instruction(OLED_COMMANDS_ENABLED),
+            Command::Instruction(SEGMENT_CONFIGURATION),
+            Command::Instruction(
+                ALTERNATIVE_SEGMENTS
+                    | match self.segment_remap {
+                        true => SEGMENT_REMAP,
+                        false => 0,
+                    },
+            ),
+            Command::Instruction(FUNCTION_SELECTION_C),
+            Command::Instruction(0),
+            Command::Instruction(CONTRAST),
+            Command::Instruction(self.contrast),
+            Command::Instruction(PHASE_LENGTH),
+            Command::Instruction(DEFAULT_PHASE_LENGTH),
+            Command::Instruction(VCOMH_DESELECT_LEVEL),
+            Command::Instruction(DEFAULT_VCOMH),
+            Command::Instruction(OLED_COMMANDS_DISABLED),
+        ]
+        .into_iter()
+        .chain(self.double_height_sequence(function_set))
+    }
+
+    #[inline]
+    fn row_offset(&self, lines: Lines, line: Lines) -> u8 {
+        extended_row_offset(lines, line)
+    }
+
+    #[inline]
+    fn function_set(&self, function_set: u8, lines: Lines) -> u8 {
+        let function_set = extended_line_function_set(function_set, lines);
+        match self.double_height {
+            Some(_) => function_set | DOUBLE_HEIGHT,
+            None => function_set & !DOUBLE_HEIGHT,
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// BLOCKING INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<I, W, K> Display<I, W, Blocking, Us2066, K>
+where
+    W: BusWidth,
+    I: BlockingInterface<W>,
+    K: Clock,
+{
+    pub fn set_contrast(&mut self, contrast: u8) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting contrast to {}", contrast);
+        self.controller_mut().contrast = contrast;
+        self.send_all(Us2066::oled_command(
+            self.function_set(),
+            CONTRAST,
+            contrast,
+        ))
+    }
+
+    // Fades the display out once within the given time interval (0 .. 15, in steps of 8 frames)
+    pub fn fade_out(&mut self, interval: u8) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Fading out display");
+        self.send_all(Us2066::oled_command(
+            self.function_set(),
+            FADE_OUT_AND_BLINKING,
+            FADE_OUT | (interval & 0x0F),
+        ))
+    }
+
+    // Fades the display out and in continuously (0 .. 15, in steps of 8 frames)
+    pub fn blink(&mut self, interval: u8) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Blinking display");
+        self.send_all(Us2066::oled_command(
+            self.function_set(),
+            FADE_OUT_AND_BLINKING,
+            BLINKING | (interval & 0x0F),
+        ))
+    }
+
+    pub fn disable_fade(&mut self) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Disabling fade out and blinking");
+        self.send_all(Us2066::oled_command(
+            self.function_set(),
+            FADE_OUT_AND_BLINKING,
+            FADE_DISABLED,
+        ))
+    }
+
+    pub fn set_double_height(
+        &mut self,
+        double_height: Option<DoubleHeight>,
+    ) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting double height to {:?}", double_height);
+        self.controller_mut().double_height = double_height;
+        self.send_all(
+            self.controller()
+                .double_height_sequence(self.function_set()),
+        )
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// ASYNC INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<I, W, K> Display<I, W, Async, Us2066, K>
+where
+    W: BusWidth,
+    I: AsyncInterface<W>,
+    K: Clock,
+{
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting contrast to {}", contrast);
+        self.controller_mut().contrast = contrast;
+        self.send_all(Us2066::oled_command(
+            self.function_set(),
+            CONTRAST,
+            contrast,
+        ))
+        .await
+    }
+
+    // Fades the display out once within the given time interval (0 .. 15, in steps of 8 frames)
+    pub async fn fade_out(&mut self, interval: u8) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Fading out display");
+        self.send_all(Us2066::oled_command(
+            self.function_set(),
+            FADE_OUT_AND_BLINKING,
+            FADE_OUT | (interval & 0x0F),
+        ))
+        .await
+    }
+
+    // Fades the display out and in continuously (0 .. 15, in steps of 8 frames)
+    pub async fn blink(&mut self, interval: u8) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Blinking display");
+        self.send_all(Us2066::oled_command(
+            self.function_set(),
+            FADE_OUT_AND_BLINKING,
+            BLINKING | (interval & 0x0F),
+        ))
+        .await
+    }
+
+    pub async fn disable_fade(&mut self) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Disabling fade out and blinking");
+        self.send_all(Us2066::oled_command(
+            self.function_set(),
+            FADE_OUT_AND_BLINKING,
+            FADE_DISABLED,
+        ))
+        .await
+    }
+
+    pub async fn set_double_height(
+        &mut self,
+        double_height: Option<DoubleHeight>,
+    ) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting double height to {:?}", double_height);
+        self.controller_mut().double_height = double_height;
+        self.send_all(
+            self.controller()
+                .double_height_sequence(self.function_set()),
+        )
+        .await
+    }
+}
@@ -0,0 +1,21 @@
+use crate::controllers::Controller;
+use crate::{Command, Lines};
+
+// Cursor/display shift instruction with the mode and power bits set
+pub(crate) const CHARACTER_MODE: u8 = 0b0001_0111;
+
+const DISPLAY_OFF: u8 = 0b0000_1000;
+
+#[derive(Default, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Ws0010 {}
+
+impl Controller for Ws0010 {
+    fn init_sequence(&self, _function_set: u8, _lines: Lines) -> impl Iterator<Item = Command> {
+        [
+            Command::Instruction(DISPLAY_OFF),
+            // Character mode with the internal power enabled
+            Command::Instruction(CHARACTER_MODE),
+        ]
+        .into_iter()
+    }
+}
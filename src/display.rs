@@ -1,32 +1,194 @@
-use crate::interfaces::{AsyncInterface, BlockingInterface, BusWidth};
-use crate::{Async, Blocking, Cursor, Font, Lines, Mode, Shift, ShiftDirection};
+use crate::batch::{Batch, BatchError, BATCH_CAPACITY};
+use crate::clock::{Clock, NoClock, Ready};
+use crate::controllers::{Controller, Hd44780};
+use crate::interfaces::{
+    AsyncInterface, AsyncRgbBacklight, BlockingInterface, BlockingRgbBacklight, BusWidth,
+};
+use crate::{Async, Blocking, Command, Cursor, Font, Lines, Mode, Shift, ShiftDirection, Timing};
 use core::fmt;
 use core::marker::PhantomData;
 
-#[repr(u8)]
-#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
-enum Commands {
-    Clear = 1,
-    Home = 2,
-    EntryModeSet = 4,
-    DisplayControl = 8,
-    Shift = 16,
-    //SetCharacterGeneratorAddress = 64,
-    SetDisplayDataAddress = 128,
+const STRING_CHUNK_SIZE: usize = 20;
+
+// Maps the character to the ROM code A00 of the HD44780, unknown characters become '?'
+pub(crate) fn character_as_byte(c: char) -> u8 {
+    match c.is_ascii() {
+        true => c as u8,
+        false => match c {
+            'ä' | 'Ä' => 0b1110_0001,
+            'ß' => 0b1110_0010,
+            'ö' | 'Ö' => 0b1110_1111,
+            'ü' | 'Ü' => 0b1111_0101,
+            '°' => 0b1101_1111,
+            _ => 0b0011_1111, // == ?
+        },
+    }
+}
+
+// DDRAM address of the position on the line. Positions beyond the DDRAM wrap around instead of
+// overflowing.
+#[inline]
+pub(crate) fn ddram_address<C: Controller>(
+    controller: &C,
+    lines: Lines,
+    line: Lines,
+    position: u8,
+) -> u8 {
+    controller.row_offset(lines, line).wrapping_add(position) & 0b0111_1111
+}
+
+// Increment flag of the entry mode
+pub(crate) const INCREMENT: u8 = 0b0000_0010;
+
+// Last DDRAM addresses of the first and second line in the two line mode, after which the
+// address counter jumps to the start of the other line
+const LINE_1_END: u8 = 0x27;
+const LINE_2_END: u8 = 0x67;
+// Last DDRAM address in the single line mode
+const SINGLE_LINE_END: u8 = 0x4F;
+
+// Range of DDRAM addresses the address counter runs through
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum DdramLayout {
+    // A single line of 80 characters
+    SingleLine,
+    // Two lines of 40 characters at 0x00 and 0x40, also used by modules with three or four lines
+    TwoLines,
+    // The full address range, over which the controllers with an extended function set spread
+    // three or four lines
+    Full,
+}
+
+impl DdramLayout {
+    pub(crate) fn new<C: Controller>(controller: &C, lines: Lines) -> Self {
+        match lines {
+            Lines::_1 => Self::SingleLine,
+            _ if controller.row_offset(lines, Lines::_2) == 0x40 => Self::TwoLines,
+            _ => Self::Full,
+        }
+    }
+
+    // Address the counter moves to from the given one
+    fn step(self, address: u8, increment: bool) -> u8 {
+        match (self, increment) {
+            (Self::SingleLine, true) if address >= SINGLE_LINE_END => 0x00,
+            (Self::SingleLine, false) if address == 0x00 => SINGLE_LINE_END,
+            (Self::TwoLines, true) if address == LINE_1_END => 0x40,
+            (Self::TwoLines, true) if address >= LINE_2_END => 0x00,
+            (Self::TwoLines, false) if address == 0x40 => LINE_1_END,
+            (Self::TwoLines, false) if address == 0x00 => LINE_2_END,
+            (_, true) => address.wrapping_add(1) & 0b0111_1111,
+            (_, false) => address.wrapping_sub(1) & 0b0111_1111,
+        }
+    }
+}
+
+// Address counter of the controller, as far as it can be followed
+#[derive(Default, Debug, Clone, Copy)]
+pub(crate) struct AddressCounter {
+    pub(crate) address: u8,
+    pub(crate) cgram: bool,
+}
+
+impl AddressCounter {
+    pub(crate) fn follow(&mut self, command: Command, entry_mode: u8, layout: DdramLayout) {
+        match command {
+            Command::Clear | Command::Home => *self = Self::default(),
+            Command::SetDdramAddress(address) => {
+                *self = Self {
+                    address,
+                    cgram: false,
+                }
+            }
+            Command::SetCgramAddress(address) => {
+                *self = Self {
+                    address,
+                    cgram: true,
+                }
+            }
+            Command::Data(_) => self.advance(1, entry_mode & INCREMENT != 0, layout),
+            Command::Shift(Shift::Cursor, direction) => {
+                self.advance(1, direction == ShiftDirection::Right, layout)
+            }
+            _ => {}
+        }
+    }
+
+    // Moves by `count` characters. The DDRAM address follows the line wrap of the controller.
+    pub(crate) fn advance(&mut self, count: usize, increment: bool, layout: DdramLayout) {
+        if self.cgram {
+            self.address = match increment {
+                true => self.address.wrapping_add(count as u8),
+                false => self.address.wrapping_sub(count as u8),
+            } & 0b0011_1111;
+            return;
+        }
+        for _ in 0..count {
+            self.address = layout.step(self.address, increment);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn command(&self) -> Command {
+        match self.cgram {
+            true => Command::SetCgramAddress(self.address),
+            false => Command::SetDdramAddress(self.address),
+        }
+    }
 }
 
 #[derive(Debug)]
-pub struct Display<I, W: BusWidth, DM: Mode> {
+pub struct Display<I, W: BusWidth, DM: Mode, C = Hd44780, K = NoClock> {
     interface: I,
+    controller: C,
     lines: Lines,
     font: Font,
     display_control: u8,
     entry_mode: u8,
+    ready: Ready<K>,
+    counter: AddressCounter,
+    // Set while an async operation is on the bus, so a dropped future leaves it set
+    in_progress: bool,
     _mode: PhantomData<DM>,
     _width: PhantomData<W>,
 }
 
-impl<I, W: BusWidth, DM: Mode> Display<I, W, DM> {
+impl<I, W: BusWidth, DM: Mode, C: Controller, K: Clock> Display<I, W, DM, C, K> {
+    #[inline]
+    pub fn with_controller<C2: Controller>(self, controller: C2) -> Display<I, W, DM, C2, K> {
+        Display {
+            interface: self.interface,
+            controller,
+            lines: self.lines,
+            font: self.font,
+            display_control: self.display_control,
+            entry_mode: self.entry_mode,
+            ready: self.ready,
+            counter: self.counter,
+            in_progress: self.in_progress,
+            _mode: PhantomData,
+            _width: PhantomData,
+        }
+    }
+
+    // Waits only for the part of the execution times that has not passed yet
+    #[inline]
+    pub fn with_clock<K2: Clock>(self, clock: K2) -> Display<I, W, DM, C, K2> {
+        Display {
+            interface: self.interface,
+            controller: self.controller,
+            lines: self.lines,
+            font: self.font,
+            display_control: self.display_control,
+            entry_mode: self.entry_mode,
+            ready: Ready::new(clock),
+            counter: self.counter,
+            in_progress: self.in_progress,
+            _mode: PhantomData,
+            _width: PhantomData,
+        }
+    }
+
     #[inline]
     pub fn with_lines(mut self, lines: Lines) -> Self {
         self.lines = lines;
@@ -57,19 +219,91 @@ impl<I, W: BusWidth, DM: Mode> Display<I, W, DM> {
         self
     }
 
-    fn character_as_byte(c: char) -> u8 {
-        match c.is_ascii() {
-            true => c as u8,
-            false => match c {
-                'ä' | 'Ä' => 0b1110_0001,
-                'ß' => 0b1110_0010,
-                'ö' | 'Ö' => 0b1110_1111,
-                'ü' | 'Ü' => 0b1111_0101,
-                '°' => 0b1101_1111,
-                _ => 0b0011_1111, // == ?
-            },
+    #[inline]
+    pub(crate) fn controller(&self) -> &C {
+        &self.controller
+    }
+
+    #[inline]
+    pub(crate) fn controller_mut(&mut self) -> &mut C {
+        &mut self.controller
+    }
+
+    #[inline]
+    pub(crate) fn interface_mut(&mut self) -> &mut I {
+        &mut self.interface
+    }
+
+    #[inline]
+    pub(crate) fn entry_mode(&self) -> u8 {
+        self.entry_mode
+    }
+
+    #[inline]
+    pub(crate) fn ddram_layout(&self) -> DdramLayout {
+        DdramLayout::new(&self.controller, self.lines)
+    }
+
+    // DDRAM address of the position on the line
+    #[inline]
+    pub(crate) fn address(&self, line: Lines, position: u8) -> u8 {
+        ddram_address(&self.controller, self.lines, line, position)
+    }
+
+    // Whether the controller has executed the last command
+    #[cfg(feature = "nb")]
+    #[inline]
+    pub(crate) fn is_ready(&mut self) -> bool {
+        self.ready.is_ready()
+    }
+
+    // Follows the state of the controller changed by a command
+    #[inline]
+    fn follow(&mut self, command: Command) {
+        self.follow_modes(command);
+        self.counter
+            .follow(command, self.entry_mode, self.ddram_layout());
+    }
+
+    // Takes over the entry mode and display control set by a command, which are restored on
+    // reinitialization
+    #[inline]
+    pub(crate) fn follow_modes(&mut self, command: Command) {
+        match command {
+            Command::EntryModeSet(entry_mode) => self.entry_mode = entry_mode,
+            Command::DisplayControl(display_control) => self.display_control = display_control,
+            _ => {}
         }
     }
+
+    pub(crate) fn function_set(&self) -> u8 {
+        let function_set = match self.font {
+            Font::_5x10 => W::WIDTH | 0b0000_0100,
+            Font::_5x8 => W::WIDTH,
+        };
+        self.controller.function_set(function_set, self.lines)
+    }
+
+    // Initialization by instruction of the HD44780 datasheet, which follows the wake-up of the
+    // bus. The controller specific instructions run right after the function set.
+    fn init_sequence(
+        controller: &C,
+        function_set: u8,
+        lines: Lines,
+        display_control: u8,
+        entry_mode: u8,
+    ) -> impl Iterator<Item = Command> + '_ {
+        [Command::FunctionSet(function_set)]
+            .into_iter()
+            .chain(controller.init_sequence(function_set, lines))
+            .chain([
+                // Display off
+                Command::DisplayControl(0),
+                Command::Clear,
+                Command::EntryModeSet(entry_mode),
+                Command::DisplayControl(display_control),
+            ])
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -84,95 +318,225 @@ where
     pub fn new(interface: I) -> Self {
         Self {
             interface,
+            controller: Hd44780::default(),
             lines: Lines::default(),
             font: Font::default(),
-            display_control: Commands::DisplayControl as u8,
-            entry_mode: Commands::EntryModeSet as u8,
+            display_control: 0,
+            entry_mode: 0,
+            ready: Ready::new(NoClock),
+            counter: AddressCounter::default(),
+            in_progress: false,
             _mode: PhantomData,
             _width: PhantomData,
         }
     }
+}
 
+impl<I, W, C, K> Display<I, W, Blocking, C, K>
+where
+    W: BusWidth,
+    I: BlockingInterface<W>,
+    C: Controller,
+    K: Clock,
+{
     pub fn init(mut self) -> Result<Self, I::Error> {
+        self.reinit()?;
+        Ok(self)
+    }
+
+    // Runs the initialization again, e.g. after the module lost its supply
+    pub(crate) fn reinit(&mut self) -> Result<(), I::Error> {
         #[cfg(feature = "log")]
         log::info!("Initializing LCD");
+        let timing = self.timing();
+        // Wait for the supply voltage to settle
+        self.interface.delay_us(timing.power_on_us);
         self.interface.initialize()?;
-        // Configure font and lines
-        let function_set = match self.font {
-            Font::_5x10 => W::WIDTH | 0b0000_0100,
-            Font::_5x8 => match self.lines {
-                Lines::_1 => W::WIDTH,
-                Lines::_2 => W::WIDTH | 0b000_1000,
-            },
+        let sequence = Self::init_sequence(
+            &self.controller,
+            self.function_set(),
+            self.lines,
+            self.display_control,
+            self.entry_mode,
+        );
+        for command in sequence {
+            Self::send_on(&mut self.interface, &mut self.ready, &timing, command)?;
+        }
+        self.counter = AddressCounter::default();
+        Ok(())
+    }
+
+    fn send_on(
+        interface: &mut I,
+        ready: &mut Ready<K>,
+        timing: &Timing,
+        command: Command,
+    ) -> Result<(), I::Error> {
+        interface.delay_us(ready.remaining_us());
+        if let Some((data, instruction)) = command.encode() {
+            interface.write(data, instruction)?;
+        }
+        ready.busy_for(command.delay_us(timing));
+        Ok(())
+    }
+
+    #[inline]
+    pub(crate) fn wait_ready(&mut self) {
+        self.interface.delay_us(self.ready.remaining_us());
+    }
+
+    // Sends a command. The next transfer waits until the controller has executed it.
+    pub fn send(&mut self, command: Command) -> Result<(), I::Error> {
+        self.send_all([command])
+    }
+
+    pub fn send_all<S>(&mut self, commands: S) -> Result<(), I::Error>
+    where
+        S: IntoIterator<Item = Command>,
+    {
+        let timing = self.timing();
+        for command in commands {
+            Self::send_on(&mut self.interface, &mut self.ready, &timing, command)?;
+            self.follow(command);
+        }
+        Ok(())
+    }
+
+    // Sends commands which move the address counter elsewhere, e.g. into the icon RAM, and
+    // points it back to the followed address afterward
+    pub(crate) fn send_detached<S>(&mut self, commands: S) -> Result<(), I::Error>
+    where
+        S: IntoIterator<Item = Command>,
+    {
+        let timing = self.timing();
+        for command in commands.into_iter().chain([self.counter.command()]) {
+            Self::send_on(&mut self.interface, &mut self.ready, &timing, command)?;
+        }
+        Ok(())
+    }
+
+    // Collects the commands issued by `f` and hands them to the interface at once. Nothing is
+    // sent if they exceed the capacity of the batch.
+    pub fn batch<F>(&mut self, f: F) -> Result<(), BatchError<I::Error>>
+    where
+        F: FnOnce(&mut Batch<'_, C>),
+    {
+        let timing = self.timing();
+        // Copied out of the batch, which borrows the controller
+        let mut buffer = [Command::Delay(0); BATCH_CAPACITY];
+        let len = {
+            let mut batch = Batch::new(&self.controller, self.lines);
+            f(&mut batch);
+            let commands = batch.commands()?;
+            buffer[..commands.len()].copy_from_slice(commands);
+            commands.len()
         };
-        self.interface.write(function_set, true)?;
-        // Configure the display
-        self.interface.write(self.display_control, true)?;
-        self.interface.write(self.entry_mode, true)?;
-        self.clear()?;
-        Ok(self)
+        let commands = &buffer[..len];
+        #[cfg(feature = "log")]
+        log::info!("Sending batch of {} commands", commands.len());
+        self.interface.delay_us(self.ready.remaining_us());
+        self.interface
+            .write_many(commands, &timing)
+            .map_err(BatchError::InterfaceError)?;
+        // The last command is executed while the caller goes on
+        let wait = commands.last().map_or(0, |c| c.delay_us(&timing));
+        self.ready.busy_for(wait);
+        for command in commands {
+            self.follow(*command);
+        }
+        Ok(())
+    }
+
+    // Timing of the interface, extended by the controller where needed
+    #[inline]
+    fn timing(&self) -> Timing {
+        self.controller.timing(*self.interface.timing())
     }
 
     pub fn clear(&mut self) -> Result<(), I::Error> {
         #[cfg(feature = "log")]
         log::info!("Clearing display");
-        self.interface.write(Commands::Clear as u8, true)?;
-        self.interface.delay_us(1500);
-        Ok(())
+        self.send(Command::Clear)
     }
 
     pub fn home(&mut self) -> Result<(), I::Error> {
         #[cfg(feature = "log")]
         log::info!("Moving cursor home");
-        self.interface.write(Commands::Home as u8, true)?;
-        Ok(())
+        self.send(Command::Home)
     }
 
     pub fn shift(&mut self, shift: Shift, shift_direction: ShiftDirection) -> Result<(), I::Error> {
         #[cfg(feature = "log")]
         log::info!("Shifting the {} to the {}", shift, shift_direction);
-        self.interface.write(
-            Commands::Shift as u8 + ((shift as u8 + shift_direction as u8) << 2),
-            true,
-        )
+        self.send(Command::Shift(shift, shift_direction))
+    }
+
+    pub fn set_cursor(&mut self, cursor: Cursor) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting cursor to {:?}", cursor);
+        self.display_control = (self.display_control & !0b0000_0011) | cursor as u8;
+        self.send(Command::DisplayControl(self.display_control))
     }
 
     pub fn pos(&mut self, line: Lines, position: u8) -> Result<(), I::Error> {
         #[cfg(feature = "log")]
         log::info!("Moving cursor to position {} on line {}", position, line);
-        let cmd = Commands::SetDisplayDataAddress as u8
-            | match line {
-                Lines::_1 => position,
-                Lines::_2 => 0x40 + position,
-            };
-        self.interface.write(cmd, true)?;
-        Ok(())
+        let address = self.address(line, position);
+        self.send(Command::SetDdramAddress(address))
+    }
+
+    // Stores the bitmap of one of the eight custom characters in the CGRAM. Each byte is a row of
+    // the character, with the pixels in the lower five bits. The address counter points into the
+    // CGRAM afterward, so the cursor has to be positioned again before writing characters.
+    pub fn set_custom_char(&mut self, location: u8, bitmap: &[u8; 8]) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting custom character {}", location);
+        self.send(Command::SetCgramAddress((location & 0b0000_0111) << 3))?;
+        self.write_bytes(bitmap)
     }
 
     #[inline]
     pub fn write_byte(&mut self, data: u8) -> Result<(), I::Error> {
-        self.interface.write(data, false)
+        self.send(Command::Data(data))
     }
 
+    #[inline]
     pub fn write_bytes(&mut self, data: &[u8]) -> Result<(), I::Error> {
-        for b in data {
-            self.write_byte(*b)?;
+        if data.is_empty() {
+            return Ok(());
         }
+        let timing = self.timing();
+        self.wait_ready();
+        self.interface.write_bytes(data, false, &timing)?;
+        self.ready.busy_for(timing.execution_us);
+        self.counter.advance(
+            data.len(),
+            self.entry_mode & INCREMENT != 0,
+            self.ddram_layout(),
+        );
         Ok(())
     }
 
     #[inline]
     pub fn write_character(&mut self, c: char) -> Result<(), I::Error> {
-        self.write_byte(Self::character_as_byte(c))
+        self.write_byte(character_as_byte(c))
     }
 
     pub fn write_string<S: AsRef<str>>(&mut self, s: S) -> Result<(), I::Error> {
         #[cfg(feature = "log")]
         log::info!("Writing string '{}' to LCD", s.as_ref());
+        // Pass the string on in chunks, so that the interface can send them at once
+        let mut buffer = [0; STRING_CHUNK_SIZE];
+        let mut len = 0;
         for c in s.as_ref().chars() {
-            self.write_character(c)?;
+            buffer[len] = character_as_byte(c);
+            len += 1;
+            if len == buffer.len() {
+                self.write_bytes(&buffer)?;
+                len = 0;
+            }
         }
-        Ok(())
+        self.write_bytes(&buffer[..len])
     }
 
     pub fn enable_backlight(&mut self) -> Result<(), I::Error> {
@@ -188,10 +552,32 @@ where
     }
 }
 
-impl<I, W> fmt::Write for Display<I, W, Blocking>
+impl<I, W, C, K> Display<I, W, Blocking, C, K>
+where
+    W: BusWidth,
+    I: BlockingInterface<W> + BlockingRgbBacklight,
+    C: Controller,
+    K: Clock,
+{
+    pub fn set_backlight_color(&mut self, red: u8, green: u8, blue: u8) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting backlight color to ({}, {}, {})", red, green, blue);
+        self.interface.set_color(red, green, blue)
+    }
+
+    pub fn set_backlight_brightness(&mut self, brightness: u8) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting backlight brightness to {}", brightness);
+        self.interface.set_brightness(brightness)
+    }
+}
+
+impl<I, W, C, K> fmt::Write for Display<I, W, Blocking, C, K>
 where
     W: BusWidth,
     I: BlockingInterface<W>,
+    C: Controller,
+    K: Clock,
 {
     #[inline]
     fn write_str(&mut self, s: &str) -> fmt::Result {
@@ -216,47 +602,189 @@ where
     pub fn new_async(interface: I) -> Self {
         Self {
             interface,
+            controller: Hd44780::default(),
             lines: Lines::default(),
             font: Font::default(),
-            display_control: Commands::DisplayControl as u8,
-            entry_mode: Commands::EntryModeSet as u8,
+            display_control: 0,
+            entry_mode: 0,
+            ready: Ready::new(NoClock),
+            counter: AddressCounter::default(),
+            in_progress: false,
             _mode: PhantomData,
             _width: PhantomData,
         }
     }
+}
 
+impl<I, W, C, K> Display<I, W, Async, C, K>
+where
+    W: BusWidth,
+    I: AsyncInterface<W>,
+    C: Controller,
+    K: Clock,
+{
     pub async fn init(mut self) -> Result<Self, I::Error> {
         #[cfg(feature = "log")]
         log::info!("Initializing LCD");
+        self.in_progress = true;
+        let timing = self.timing();
+        // Wait for the supply voltage to settle
+        self.interface.delay_us(timing.power_on_us).await;
         self.interface.initialize().await?;
-        // Configure font and lines
-        let function_set = match self.font {
-            Font::_5x10 => 0b0010_0100,
-            Font::_5x8 => match self.lines {
-                Lines::_1 => 0b0010_0000,
-                Lines::_2 => 0b0010_1000,
-            },
-        };
-        self.interface.write(function_set, true).await?;
-        // Configure the display
-        self.interface.write(self.display_control, true).await?;
-        self.interface.write(self.entry_mode, true).await?;
-        self.clear().await?;
+        let sequence = Self::init_sequence(
+            &self.controller,
+            self.function_set(),
+            self.lines,
+            self.display_control,
+            self.entry_mode,
+        );
+        for command in sequence {
+            Self::send_on(&mut self.interface, &mut self.ready, &timing, command).await?;
+        }
+        self.counter = AddressCounter::default();
+        self.in_progress = false;
         Ok(self)
     }
 
+    async fn send_on(
+        interface: &mut I,
+        ready: &mut Ready<K>,
+        timing: &Timing,
+        command: Command,
+    ) -> Result<(), I::Error> {
+        interface.delay_us(ready.remaining_us()).await;
+        if let Some((data, instruction)) = command.encode() {
+            interface.write(data, instruction).await?;
+        }
+        ready.busy_for(command.delay_us(timing));
+        Ok(())
+    }
+
+    #[inline]
+    async fn wait_ready(&mut self) {
+        self.interface.delay_us(self.ready.remaining_us()).await;
+    }
+
+    // Recovers from an interrupted operation and marks the start of the next one
+    async fn begin(&mut self) -> Result<(), I::Error> {
+        if self.in_progress {
+            self.resync().await?;
+        }
+        self.in_progress = true;
+        Ok(())
+    }
+
+    // Brings the bus back in step after an operation was interrupted, e.g. by dropping its
+    // future between the two nibbles of a byte, and restores the state of the controller. The
+    // controller specific instructions are replayed as well, as the operation may have left the
+    // extended instruction set selected or changed its registers.
+    async fn resync(&mut self) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::warn!("Resynchronizing the LCD after an interrupted operation");
+        let timing = self.timing();
+        let function_set = self.function_set();
+        self.wait_ready().await;
+        self.interface.initialize().await?;
+        let restore = [Command::FunctionSet(function_set)]
+            .into_iter()
+            .chain(self.controller.init_sequence(function_set, self.lines))
+            .chain([
+                Command::DisplayControl(self.display_control),
+                Command::EntryModeSet(self.entry_mode),
+                self.counter.command(),
+            ]);
+        for command in restore {
+            Self::send_on(&mut self.interface, &mut self.ready, &timing, command).await?;
+        }
+        Ok(())
+    }
+
+    // Sends a command. The next transfer waits until the controller has executed it.
+    pub async fn send(&mut self, command: Command) -> Result<(), I::Error> {
+        self.send_all([command]).await
+    }
+
+    pub async fn send_all<S>(&mut self, commands: S) -> Result<(), I::Error>
+    where
+        S: IntoIterator<Item = Command>,
+    {
+        self.begin().await?;
+        let timing = self.timing();
+        for command in commands {
+            Self::send_on(&mut self.interface, &mut self.ready, &timing, command).await?;
+            self.follow(command);
+        }
+        self.in_progress = false;
+        Ok(())
+    }
+
+    // Sends commands which move the address counter elsewhere, e.g. into the icon RAM, and
+    // points it back to the followed address afterward
+    pub(crate) async fn send_detached<S>(&mut self, commands: S) -> Result<(), I::Error>
+    where
+        S: IntoIterator<Item = Command>,
+    {
+        self.begin().await?;
+        let timing = self.timing();
+        for command in commands.into_iter().chain([self.counter.command()]) {
+            Self::send_on(&mut self.interface, &mut self.ready, &timing, command).await?;
+        }
+        self.in_progress = false;
+        Ok(())
+    }
+
+    // Collects the commands issued by `f` and hands them to the interface at once. Nothing is
+    // sent if they exceed the capacity of the batch.
+    pub async fn batch<F>(&mut self, f: F) -> Result<(), BatchError<I::Error>>
+    where
+        F: FnOnce(&mut Batch<'_, C>),
+    {
+        let timing = self.timing();
+        // Copied out of the batch, which borrows the controller needed by a resync
+        let mut buffer = [Command::Delay(0); BATCH_CAPACITY];
+        let len = {
+            let mut batch = Batch::new(&self.controller, self.lines);
+            f(&mut batch);
+            let commands = batch.commands()?;
+            buffer[..commands.len()].copy_from_slice(commands);
+            commands.len()
+        };
+        let commands = &buffer[..len];
+        // Nothing has been sent on overflow, so only now can the operation be interrupted
+        self.begin().await.map_err(BatchError::InterfaceError)?;
+        #[cfg(feature = "log")]
+        log::info!("Sending batch of {} commands", commands.len());
+        self.interface.delay_us(self.ready.remaining_us()).await;
+        self.interface
+            .write_many(commands, &timing)
+            .await
+            .map_err(BatchError::InterfaceError)?;
+        // The last command is executed while the caller goes on
+        let wait = commands.last().map_or(0, |c| c.delay_us(&timing));
+        self.ready.busy_for(wait);
+        for command in commands {
+            self.follow(*command);
+        }
+        self.in_progress = false;
+        Ok(())
+    }
+
+    // Timing of the interface, extended by the controller where needed
+    #[inline]
+    fn timing(&self) -> Timing {
+        self.controller.timing(*self.interface.timing())
+    }
+
     pub async fn clear(&mut self) -> Result<(), I::Error> {
         #[cfg(feature = "log")]
         log::info!("Clearing display");
-        self.interface.write(Commands::Clear as u8, true).await?;
-        self.interface.delay_us(1500).await;
-        Ok(())
+        self.send(Command::Clear).await
     }
 
     pub async fn home(&mut self) -> Result<(), I::Error> {
         #[cfg(feature = "log")]
         log::info!("Moving cursor home");
-        self.interface.write(Commands::Home as u8, true).await
+        self.send(Command::Home).await
     }
 
     pub async fn shift(
@@ -266,49 +794,83 @@ where
     ) -> Result<(), I::Error> {
         #[cfg(feature = "log")]
         log::info!("Shifting the {} to the {}", shift, shift_direction);
-        self.interface
-            .write(
-                Commands::Shift as u8 + ((shift as u8 + shift_direction as u8) << 2),
-                true,
-            )
+        self.send(Command::Shift(shift, shift_direction)).await
+    }
+
+    pub async fn set_cursor(&mut self, cursor: Cursor) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting cursor to {:?}", cursor);
+        self.display_control = (self.display_control & !0b0000_0011) | cursor as u8;
+        self.send(Command::DisplayControl(self.display_control))
             .await
     }
 
     pub async fn pos(&mut self, line: Lines, position: u8) -> Result<(), I::Error> {
         #[cfg(feature = "log")]
         log::info!("Moving cursor to position {} on line {}", position, line);
-        let cmd = Commands::SetDisplayDataAddress as u8
-            | match line {
-                Lines::_1 => position,
-                Lines::_2 => 0x40 + position,
-            };
-        self.interface.write(cmd, true).await
+        let address = self.address(line, position);
+        self.send(Command::SetDdramAddress(address)).await
+    }
+
+    // Stores the bitmap of one of the eight custom characters in the CGRAM. Each byte is a row of
+    // the character, with the pixels in the lower five bits. The address counter points into the
+    // CGRAM afterward, so the cursor has to be positioned again before writing characters.
+    pub async fn set_custom_char(
+        &mut self,
+        location: u8,
+        bitmap: &[u8; 8],
+    ) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting custom character {}", location);
+        self.send(Command::SetCgramAddress((location & 0b0000_0111) << 3))
+            .await?;
+        self.write_bytes(bitmap).await
     }
 
     #[inline]
     pub async fn write_byte(&mut self, data: u8) -> Result<(), I::Error> {
-        self.interface.write(data, false).await
+        self.send(Command::Data(data)).await
     }
 
+    #[inline]
     pub async fn write_bytes(&mut self, data: &[u8]) -> Result<(), I::Error> {
-        for b in data {
-            self.write_byte(*b).await?;
+        if data.is_empty() {
+            return Ok(());
         }
+        let timing = self.timing();
+        self.begin().await?;
+        self.wait_ready().await;
+        self.interface.write_bytes(data, false, &timing).await?;
+        self.ready.busy_for(timing.execution_us);
+        self.counter.advance(
+            data.len(),
+            self.entry_mode & INCREMENT != 0,
+            self.ddram_layout(),
+        );
+        self.in_progress = false;
         Ok(())
     }
 
     #[inline]
     pub async fn write_character(&mut self, c: char) -> Result<(), I::Error> {
-        self.write_byte(Self::character_as_byte(c)).await
+        self.write_byte(character_as_byte(c)).await
     }
 
     pub async fn write_string<S: AsRef<str>>(&mut self, s: S) -> Result<(), I::Error> {
         #[cfg(feature = "log")]
         log::info!("Writing string '{}' to LCD", s.as_ref());
+        // Pass the string on in chunks, so that the interface can send them at once
+        let mut buffer = [0; STRING_CHUNK_SIZE];
+        let mut len = 0;
         for c in s.as_ref().chars() {
-            self.write_character(c).await?;
+            buffer[len] = character_as_byte(c);
+            len += 1;
+            if len == buffer.len() {
+                self.write_bytes(&buffer).await?;
+                len = 0;
+            }
         }
-        Ok(())
+        self.write_bytes(&buffer[..len]).await
     }
 
     pub async fn enable_backlight(&mut self) -> Result<(), I::Error> {
@@ -323,3 +885,90 @@ where
         self.interface.backlight(false).await
     }
 }
+
+impl<I, W, C, K> Display<I, W, Async, C, K>
+where
+    W: BusWidth,
+    I: AsyncInterface<W> + AsyncRgbBacklight,
+    C: Controller,
+    K: Clock,
+{
+    pub async fn set_backlight_color(
+        &mut self,
+        red: u8,
+        green: u8,
+        blue: u8,
+    ) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting backlight color to ({}, {}, {})", red, green, blue);
+        self.interface.set_color(red, green, blue).await
+    }
+
+    pub async fn set_backlight_brightness(&mut self, brightness: u8) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Setting backlight brightness to {}", brightness);
+        self.interface.set_brightness(brightness).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controllers::St7032;
+    use crate::interfaces::{BlockOn, SpinBlockOn};
+    use crate::mock::Recorder;
+
+    #[test]
+    fn overflowing_batch_does_not_resync() {
+        let mut display = Display::new_async(Recorder::default());
+        SpinBlockOn.block_on(async {
+            let result = display
+                .batch(|batch| {
+                    (0..=BATCH_CAPACITY).for_each(|_| {
+                        batch.write_byte(b'x');
+                    })
+                })
+                .await;
+            assert!(matches!(result, Err(BatchError::Overflow)));
+            display.write_byte(b'a').await.unwrap();
+        });
+        let bus = display.interface_mut();
+        assert_eq!(bus.initializations, 0);
+        assert_eq!(bus.writes, [(b'a', false)]);
+    }
+
+    #[test]
+    fn detached_commands_restore_the_cursor() {
+        let mut display =
+            Display::new(Recorder::default()).with_shift(Shift::Cursor, ShiftDirection::Right);
+        display.send(Command::SetDdramAddress(0x45)).unwrap();
+        display.write_byte(b'a').unwrap();
+        display.send_detached([Command::Instruction(0x40)]).unwrap();
+        assert_eq!(display.interface_mut().instructions(), [0xC5, 0x40, 0xC6]);
+    }
+
+    #[test]
+    fn resync_replays_the_controller_sequence() {
+        let mut display =
+            Display::new_async(Recorder::default()).with_controller(St7032::default());
+        // Left behind by a dropped future
+        display.in_progress = true;
+        SpinBlockOn.block_on(display.write_byte(b'a')).unwrap();
+        let function_set = display.function_set();
+        let expected = [Command::FunctionSet(function_set)]
+            .into_iter()
+            .chain(St7032::default().init_sequence(function_set, Lines::_1))
+            .chain([
+                Command::DisplayControl(0),
+                Command::EntryModeSet(0),
+                Command::SetDdramAddress(0),
+            ])
+            .filter_map(|command| command.encode())
+            .map(|(instruction, _)| instruction)
+            .collect::<Vec<_>>();
+        let bus = display.interface_mut();
+        assert_eq!(bus.initializations, 1);
+        assert_eq!(bus.instructions(), expected);
+        assert_eq!(bus.data(), b"a");
+    }
+}
@@ -0,0 +1,142 @@
+use crate::controllers::Controller;
+use crate::interfaces::{BlockingInterface, BusWidth, ErrorKind, ErrorType};
+use crate::{Blocking, Clock, Command, Cursor, Display, Lines, Shift, ShiftDirection};
+use core::fmt;
+use core::fmt::Debug;
+
+/// Error of a `DynLcd`, with the type of the interface error erased.
+///
+/// Only the kind of the original error is kept. The error itself is logged with the `log`
+/// feature.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DynLcdError {
+    kind: ErrorKind,
+}
+
+impl DynLcdError {
+    #[inline]
+    fn erase<I: ErrorType>(error: I::Error) -> Self
+    where
+        I::Error: Debug,
+    {
+        #[cfg(feature = "log")]
+        log::error!("LCD interface error: {:?}", error);
+        Self {
+            kind: I::error_kind(&error),
+        }
+    }
+
+    #[inline]
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for DynLcdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LCD interface error: {:?}", self.kind)
+    }
+}
+
+/// Object safe version of the blocking `Display` API.
+///
+/// Displays on different interfaces or controllers can be used through `&mut dyn DynLcd`, e.g.
+/// when the wiring is only known at runtime. The display has to be initialized beforehand.
+pub trait DynLcd: fmt::Write {
+    fn send(&mut self, command: Command) -> Result<(), DynLcdError>;
+    fn send_all(&mut self, commands: &[Command]) -> Result<(), DynLcdError>;
+    fn clear(&mut self) -> Result<(), DynLcdError>;
+    fn home(&mut self) -> Result<(), DynLcdError>;
+    fn shift(&mut self, shift: Shift, shift_direction: ShiftDirection) -> Result<(), DynLcdError>;
+    fn set_cursor(&mut self, cursor: Cursor) -> Result<(), DynLcdError>;
+    fn pos(&mut self, line: Lines, position: u8) -> Result<(), DynLcdError>;
+    fn set_custom_char(&mut self, location: u8, bitmap: &[u8; 8]) -> Result<(), DynLcdError>;
+    fn write_byte(&mut self, data: u8) -> Result<(), DynLcdError>;
+    fn write_bytes(&mut self, data: &[u8]) -> Result<(), DynLcdError>;
+    fn write_character(&mut self, c: char) -> Result<(), DynLcdError>;
+    fn write_string(&mut self, s: &str) -> Result<(), DynLcdError>;
+    fn enable_backlight(&mut self) -> Result<(), DynLcdError>;
+    fn disable_backlight(&mut self) -> Result<(), DynLcdError>;
+}
+
+impl<I, W, C, K> DynLcd for Display<I, W, Blocking, C, K>
+where
+    W: BusWidth,
+    I: BlockingInterface<W>,
+    I::Error: Debug,
+    C: Controller,
+    K: Clock,
+{
+    #[inline]
+    fn send(&mut self, command: Command) -> Result<(), DynLcdError> {
+        Display::<I, W, Blocking, C, K>::send(self, command).map_err(DynLcdError::erase::<I>)
+    }
+
+    #[inline]
+    fn send_all(&mut self, commands: &[Command]) -> Result<(), DynLcdError> {
+        Display::<I, W, Blocking, C, K>::send_all(self, commands.iter().copied())
+            .map_err(DynLcdError::erase::<I>)
+    }
+
+    #[inline]
+    fn clear(&mut self) -> Result<(), DynLcdError> {
+        Display::<I, W, Blocking, C, K>::clear(self).map_err(DynLcdError::erase::<I>)
+    }
+
+    #[inline]
+    fn home(&mut self) -> Result<(), DynLcdError> {
+        Display::<I, W, Blocking, C, K>::home(self).map_err(DynLcdError::erase::<I>)
+    }
+
+    #[inline]
+    fn shift(&mut self, shift: Shift, shift_direction: ShiftDirection) -> Result<(), DynLcdError> {
+        Display::<I, W, Blocking, C, K>::shift(self, shift, shift_direction)
+            .map_err(DynLcdError::erase::<I>)
+    }
+
+    #[inline]
+    fn set_cursor(&mut self, cursor: Cursor) -> Result<(), DynLcdError> {
+        Display::<I, W, Blocking, C, K>::set_cursor(self, cursor).map_err(DynLcdError::erase::<I>)
+    }
+
+    #[inline]
+    fn pos(&mut self, line: Lines, position: u8) -> Result<(), DynLcdError> {
+        Display::<I, W, Blocking, C, K>::pos(self, line, position).map_err(DynLcdError::erase::<I>)
+    }
+
+    #[inline]
+    fn set_custom_char(&mut self, location: u8, bitmap: &[u8; 8]) -> Result<(), DynLcdError> {
+        Display::<I, W, Blocking, C, K>::set_custom_char(self, location, bitmap)
+            .map_err(DynLcdError::erase::<I>)
+    }
+
+    #[inline]
+    fn write_byte(&mut self, data: u8) -> Result<(), DynLcdError> {
+        Display::<I, W, Blocking, C, K>::write_byte(self, data).map_err(DynLcdError::erase::<I>)
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, data: &[u8]) -> Result<(), DynLcdError> {
+        Display::<I, W, Blocking, C, K>::write_bytes(self, data).map_err(DynLcdError::erase::<I>)
+    }
+
+    #[inline]
+    fn write_character(&mut self, c: char) -> Result<(), DynLcdError> {
+        Display::<I, W, Blocking, C, K>::write_character(self, c).map_err(DynLcdError::erase::<I>)
+    }
+
+    #[inline]
+    fn write_string(&mut self, s: &str) -> Result<(), DynLcdError> {
+        Display::<I, W, Blocking, C, K>::write_string(self, s).map_err(DynLcdError::erase::<I>)
+    }
+
+    #[inline]
+    fn enable_backlight(&mut self) -> Result<(), DynLcdError> {
+        Display::<I, W, Blocking, C, K>::enable_backlight(self).map_err(DynLcdError::erase::<I>)
+    }
+
+    #[inline]
+    fn disable_backlight(&mut self) -> Result<(), DynLcdError> {
+        Display::<I, W, Blocking, C, K>::disable_backlight(self).map_err(DynLcdError::erase::<I>)
+    }
+}
@@ -0,0 +1,222 @@
+use crate::controllers::{Ws0010, CHARACTER_MODE};
+use crate::display::INCREMENT;
+use crate::interfaces::{AsyncInterface, BlockingInterface, BusWidth};
+use crate::{Async, Blocking, Clock, Command, Display, Mode, NoClock};
+use core::convert::Infallible;
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{OriginDimensions, Size};
+use embedded_graphics_core::pixelcolor::BinaryColor;
+use embedded_graphics_core::Pixel;
+
+// Cursor/display shift instruction with the mode and power bits set
+const GRAPHICS_MODE: u8 = 0b0001_1111;
+// Graphics RAM addresses
+const GRAPHICS_COLUMN: u8 = 0b1000_0000;
+const GRAPHICS_ROW: u8 = 0b0100_0000;
+// The rows are written from left to right
+const INTO_GRAPHICS: [Command; 2] = [
+    Command::Instruction(GRAPHICS_MODE),
+    Command::EntryModeSet(INCREMENT),
+];
+
+// The graphics RAM is organized in two rows of 8 pixels, the LSB being the topmost pixel
+const ROWS: usize = 2;
+const HEIGHT: usize = 8 * ROWS;
+// Width of the graphics RAM
+const MAX_COLUMNS: usize = 100;
+
+#[derive(Debug)]
+pub struct GraphicsDisplay<
+    I,
+    W: BusWidth,
+    DM: Mode,
+    const COLUMNS: usize = MAX_COLUMNS,
+    K = NoClock,
+> {
+    display: Display<I, W, DM, Ws0010, K>,
+    framebuffer: [[u8; COLUMNS]; ROWS],
+    // Entry mode of the character mode, the rows are written incrementing
+    entry_mode: u8,
+}
+
+impl<I, W: BusWidth, DM: Mode, const COLUMNS: usize, K> GraphicsDisplay<I, W, DM, COLUMNS, K> {
+    #[inline]
+    fn new(display: Display<I, W, DM, Ws0010, K>, entry_mode: u8) -> Self {
+        const {
+            assert!(
+                COLUMNS <= MAX_COLUMNS,
+                "The WS0010 graphics RAM has 100 columns"
+            )
+        };
+        Self {
+            display,
+            framebuffer: [[0; COLUMNS]; ROWS],
+            entry_mode,
+        }
+    }
+
+    #[inline]
+    fn character_mode(&self) -> [Command; 2] {
+        [
+            Command::Instruction(CHARACTER_MODE),
+            Command::EntryModeSet(self.entry_mode),
+        ]
+    }
+
+    #[inline]
+    fn row_address(row: usize) -> [Command; 2] {
+        [
+            Command::Instruction(GRAPHICS_ROW | row as u8),
+            Command::Instruction(GRAPHICS_COLUMN),
+        ]
+    }
+}
+
+impl<I, W: BusWidth, DM: Mode, const COLUMNS: usize, K> OriginDimensions
+    for GraphicsDisplay<I, W, DM, COLUMNS, K>
+{
+    #[inline]
+    fn size(&self) -> Size {
+        Size::new(COLUMNS as u32, HEIGHT as u32)
+    }
+}
+
+impl<I, W: BusWidth, DM: Mode, const COLUMNS: usize, K> DrawTarget
+    for GraphicsDisplay<I, W, DM, COLUMNS, K>
+{
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<P>(&mut self, pixels: P) -> Result<(), Self::Error>
+    where
+        P: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (usize::try_from(point.x), usize::try_from(point.y)) else {
+                continue;
+            };
+            if x >= COLUMNS || y >= HEIGHT {
+                continue;
+            }
+            let byte = &mut self.framebuffer[y / 8][x];
+            match color {
+                BinaryColor::On => *byte |= 1 << (y % 8),
+                BinaryColor::Off => *byte &= !(1 << (y % 8)),
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let fill = match color {
+            BinaryColor::On => 0xFF,
+            BinaryColor::Off => 0x00,
+        };
+        self.framebuffer = [[fill; COLUMNS]; ROWS];
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// BLOCKING INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<I, W, K> Display<I, W, Blocking, Ws0010, K>
+where
+    W: BusWidth,
+    I: BlockingInterface<W>,
+    K: Clock,
+{
+    // Switches the display into the graphics mode. The graphics RAM is cleared.
+    pub fn into_graphics<const COLUMNS: usize>(
+        mut self,
+    ) -> Result<GraphicsDisplay<I, W, Blocking, COLUMNS, K>, I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Switching to graphics mode");
+        let entry_mode = self.entry_mode();
+        self.send_all(INTO_GRAPHICS)?;
+        let mut graphics = GraphicsDisplay::new(self, entry_mode);
+        graphics.flush()?;
+        Ok(graphics)
+    }
+}
+
+impl<I, W, const COLUMNS: usize, K> GraphicsDisplay<I, W, Blocking, COLUMNS, K>
+where
+    W: BusWidth,
+    I: BlockingInterface<W>,
+    K: Clock,
+{
+    // Writes the framebuffer to the graphics RAM
+    pub fn flush(&mut self) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::debug!("Flushing framebuffer");
+        for (row, data) in self.framebuffer.iter().enumerate() {
+            self.display.send_all(Self::row_address(row))?;
+            self.display.write_bytes(data)?;
+        }
+        Ok(())
+    }
+
+    // Switches the display back into the character mode. The display is cleared.
+    pub fn into_character(self) -> Result<Display<I, W, Blocking, Ws0010, K>, I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Switching to character mode");
+        let commands = self.character_mode();
+        let mut display = self.display;
+        display.send_all(commands)?;
+        display.clear()?;
+        Ok(display)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// ASYNC INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<I, W, K> Display<I, W, Async, Ws0010, K>
+where
+    W: BusWidth,
+    I: AsyncInterface<W>,
+    K: Clock,
+{
+    // Switches the display into the graphics mode. The graphics RAM is cleared.
+    pub async fn into_graphics<const COLUMNS: usize>(
+        mut self,
+    ) -> Result<GraphicsDisplay<I, W, Async, COLUMNS, K>, I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Switching to graphics mode");
+        let entry_mode = self.entry_mode();
+        self.send_all(INTO_GRAPHICS).await?;
+        let mut graphics = GraphicsDisplay::new(self, entry_mode);
+        graphics.flush().await?;
+        Ok(graphics)
+    }
+}
+
+impl<I, W, const COLUMNS: usize, K> GraphicsDisplay<I, W, Async, COLUMNS, K>
+where
+    W: BusWidth,
+    I: AsyncInterface<W>,
+    K: Clock,
+{
+    // Writes the framebuffer to the graphics RAM
+    pub async fn flush(&mut self) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::debug!("Flushing framebuffer");
+        for (row, data) in self.framebuffer.iter().enumerate() {
+            self.display.send_all(Self::row_address(row)).await?;
+            self.display.write_bytes(data).await?;
+        }
+        Ok(())
+    }
+
+    // Switches the display back into the character mode. The display is cleared.
+    pub async fn into_character(self) -> Result<Display<I, W, Async, Ws0010, K>, I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("Switching to character mode");
+        let commands = self.character_mode();
+        let mut display = self.display;
+        display.send_all(commands).await?;
+        display.clear().await?;
+        Ok(display)
+    }
+}
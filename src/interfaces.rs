@@ -1,4 +1,5 @@
 use crate::private::Sealed;
+use crate::{Command, Timing};
 
 pub trait BusWidth: Sealed {
     const WIDTH: u8;
@@ -19,8 +20,83 @@ impl BusWidth for EightBitBus {
     const WIDTH: u8 = 0b0011_0000;
 }
 
+/// Kind of an interface error, independent of the HAL types behind the interface.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    // The I2C transfer failed, e.g. because the module did not acknowledge it
+    I2c(embedded_hal::i2c::ErrorKind),
+    Spi(embedded_hal::spi::ErrorKind),
+    Pin(embedded_hal::digital::ErrorKind),
+    #[cfg(feature = "io")]
+    Io(embedded_io::ErrorKind),
+    // The bus call did not complete in time
+    Timeout,
+    Other,
+}
+
+// Sends the commands one by one with `write`, waiting for the execution of each before sending
+// the next. The wait after the last command is left to the caller, which may have spent part of
+// it already. This backs the default `write_bytes` and `write_many` of the bus traits, interfaces
+// whose transfers take longer than the execution or which combine transfers override those.
+fn write_paced<B, E, F>(
+    bus: &mut B,
+    commands: impl IntoIterator<Item = Command>,
+    timing: &Timing,
+    mut write: F,
+) -> Result<(), E>
+where
+    B: embedded_hal::delay::DelayNs + ?Sized,
+    F: FnMut(&mut B, u8, bool) -> Result<(), E>,
+{
+    let mut wait = 0;
+    for command in commands {
+        bus.delay_us(wait);
+        if let Some((data, instruction)) = command.encode() {
+            write(bus, data, instruction)?;
+        }
+        wait = command.delay_us(timing);
+    }
+    Ok(())
+}
+
+async fn write_paced_async<B, E, F>(
+    bus: &mut B,
+    commands: impl IntoIterator<Item = Command>,
+    timing: &Timing,
+    mut write: F,
+) -> Result<(), E>
+where
+    B: embedded_hal_async::delay::DelayNs + ?Sized,
+    F: AsyncFnMut(&mut B, u8, bool) -> Result<(), E>,
+{
+    let mut wait = 0;
+    for command in commands {
+        bus.delay_us(wait).await;
+        if let Some((data, instruction)) = command.encode() {
+            write(bus, data, instruction).await?;
+        }
+        wait = command.delay_us(timing);
+    }
+    Ok(())
+}
+
+// Bytes of `write_bytes` as commands
+fn byte_commands(data: &[u8], command: bool) -> impl Iterator<Item = Command> + '_ {
+    data.iter().map(move |b| match command {
+        true => Command::Instruction(*b),
+        false => Command::Data(*b),
+    })
+}
+
 pub trait ErrorType {
     type Error;
+
+    // Classifies an error of the interface
+    #[inline]
+    fn error_kind(_error: &Self::Error) -> ErrorKind {
+        ErrorKind::Other
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -29,16 +105,59 @@ pub trait ErrorType {
 pub trait BlockingBus<Width: BusWidth>: ErrorType + embedded_hal::delay::DelayNs {
     fn initialize(&mut self) -> Result<(), Self::Error>;
     fn write(&mut self, data: u8, command: bool) -> Result<(), Self::Error>;
+
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &Timing::HD44780
+    }
+
+    fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        write_paced(
+            self,
+            byte_commands(data, command),
+            timing,
+            <Self as BlockingBus<Width>>::write,
+        )
+    }
+
+    fn write_many(&mut self, commands: &[Command], timing: &Timing) -> Result<(), Self::Error> {
+        write_paced(
+            self,
+            commands.iter().copied(),
+            timing,
+            <Self as BlockingBus<Width>>::write,
+        )
+    }
 }
 
 pub trait BlockingInterface<Width: BusWidth>: BlockingBus<Width> {
     fn backlight(&mut self, enable: bool) -> Result<(), Self::Error>;
 }
 
+// Interfaces able to tell whether the module answers on the bus
+pub trait BlockingProbe {
+    fn probe(&mut self) -> bool;
+}
+
+pub trait BlockingRgbBacklight: ErrorType {
+    fn set_color(&mut self, red: u8, green: u8, blue: u8) -> Result<(), Self::Error>;
+    fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error>;
+}
+
 pub trait Blocking4BitBus: ErrorType + embedded_hal::delay::DelayNs {
     fn write_nibble(&mut self, data: u8) -> Result<(), Self::Error>;
     fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error>;
 
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &Timing::HD44780
+    }
+
     #[inline]
     fn write_nibble_logged(&mut self, data: u8) -> Result<(), Self::Error> {
         #[cfg(feature = "log")]
@@ -51,21 +170,56 @@ pub trait Blocking4BitBus: ErrorType + embedded_hal::delay::DelayNs {
         log::debug!("Writing '{:#010b}' to LCD Display", data);
         // Set command / data mode
         self.set_command_mode(command)?;
+        self.delay_ns(self.timing().setup_ns);
         // Write the data in two nibbles in MSB first order.
         self.write_nibble_logged(data >> 4)?;
         self.write_nibble_logged(data)
     }
 
+    fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        write_paced(
+            self,
+            byte_commands(data, command),
+            timing,
+            <Self as Blocking4BitBus>::write,
+        )
+    }
+
+    fn write_many(&mut self, commands: &[Command], timing: &Timing) -> Result<(), Self::Error> {
+        write_paced(
+            self,
+            commands.iter().copied(),
+            timing,
+            <Self as Blocking4BitBus>::write,
+        )
+    }
+
     fn initialize(&mut self) -> Result<(), Self::Error> {
+        self.set_command_mode(true)?;
+        self.delay_ns(self.timing().setup_ns);
+        // Wake up in 8-bit mode, then switch to 4-bit mode
         self.write_nibble_logged(0b0011)?;
-        self.delay_us(4500);
+        self.delay_us(self.timing().init_long_us);
         self.write_nibble_logged(0b0011)?;
-        self.delay_us(150);
+        self.delay_us(self.timing().init_short_us);
         self.write_nibble_logged(0b0011)?;
-        self.write_nibble_logged(0b0010)
+        self.delay_us(self.timing().execution_us);
+        self.write_nibble_logged(0b0010)?;
+        self.delay_us(self.timing().execution_us);
+        Ok(())
     }
 }
 impl<T: Blocking4BitBus> BlockingBus<FourBitBus> for T {
+    #[inline]
+    fn timing(&self) -> &Timing {
+        Blocking4BitBus::timing(self)
+    }
+
     #[inline]
     fn initialize(&mut self) -> Result<(), Self::Error> {
         Blocking4BitBus::initialize(self)
@@ -75,6 +229,21 @@ impl<T: Blocking4BitBus> BlockingBus<FourBitBus> for T {
     fn write(&mut self, data: u8, command: bool) -> Result<(), Self::Error> {
         Blocking4BitBus::write(self, data, command)
     }
+
+    #[inline]
+    fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        Blocking4BitBus::write_bytes(self, data, command, timing)
+    }
+
+    #[inline]
+    fn write_many(&mut self, commands: &[Command], timing: &Timing) -> Result<(), Self::Error> {
+        Blocking4BitBus::write_many(self, commands, timing)
+    }
 }
 
 pub trait Blocking8BitBus: ErrorType + embedded_hal::delay::DelayNs {
@@ -82,26 +251,61 @@ pub trait Blocking8BitBus: ErrorType + embedded_hal::delay::DelayNs {
 
     fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error>;
 
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &Timing::HD44780
+    }
+
     fn write(&mut self, data: u8, command: bool) -> Result<(), Self::Error> {
         #[cfg(feature = "log")]
         log::debug!("Writing '{:#010b}' to LCD Display", data);
         // Set command / data mode
         self.set_command_mode(command)?;
-        self.delay_ns(60);
+        self.delay_ns(self.timing().setup_ns);
 
         // Write the data
         self.write_byte(data)
     }
 
+    fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        write_paced(
+            self,
+            byte_commands(data, command),
+            timing,
+            <Self as Blocking8BitBus>::write,
+        )
+    }
+
+    fn write_many(&mut self, commands: &[Command], timing: &Timing) -> Result<(), Self::Error> {
+        write_paced(
+            self,
+            commands.iter().copied(),
+            timing,
+            <Self as Blocking8BitBus>::write,
+        )
+    }
+
     fn initialize(&mut self) -> Result<(), Self::Error> {
         self.write(0b0011_0000, true)?;
-        self.delay_us(4500);
+        self.delay_us(self.timing().init_long_us);
+        self.write(0b0011_0000, true)?;
+        self.delay_us(self.timing().init_short_us);
         self.write(0b0011_0000, true)?;
-        self.delay_us(150);
-        self.write(0b0011_0000, true)
+        self.delay_us(self.timing().execution_us);
+        Ok(())
     }
 }
 impl<T: Blocking8BitBus> BlockingBus<EightBitBus> for T {
+    #[inline]
+    fn timing(&self) -> &Timing {
+        Blocking8BitBus::timing(self)
+    }
+
     #[inline]
     fn initialize(&mut self) -> Result<(), Self::Error> {
         Blocking8BitBus::initialize(self)
@@ -111,6 +315,21 @@ impl<T: Blocking8BitBus> BlockingBus<EightBitBus> for T {
     fn write(&mut self, data: u8, command: bool) -> Result<(), Self::Error> {
         Blocking8BitBus::write(self, data, command)
     }
+
+    #[inline]
+    fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        Blocking8BitBus::write_bytes(self, data, command, timing)
+    }
+
+    #[inline]
+    fn write_many(&mut self, commands: &[Command], timing: &Timing) -> Result<(), Self::Error> {
+        Blocking8BitBus::write_many(self, commands, timing)
+    }
 }
 // -------------------------------------------------------------------------------------------------
 // ASYNC INTERFACE
@@ -118,16 +337,54 @@ impl<T: Blocking8BitBus> BlockingBus<EightBitBus> for T {
 pub trait AsyncBus<Width: BusWidth>: ErrorType + embedded_hal_async::delay::DelayNs {
     async fn initialize(&mut self) -> Result<(), Self::Error>;
     async fn write(&mut self, data: u8, command: bool) -> Result<(), Self::Error>;
+
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &Timing::HD44780
+    }
+
+    async fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        let write = async |bus: &mut Self, data, command| {
+            <Self as AsyncBus<Width>>::write(bus, data, command).await
+        };
+        write_paced_async(self, byte_commands(data, command), timing, write).await
+    }
+
+    async fn write_many(
+        &mut self,
+        commands: &[Command],
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        let write = async |bus: &mut Self, data, command| {
+            <Self as AsyncBus<Width>>::write(bus, data, command).await
+        };
+        write_paced_async(self, commands.iter().copied(), timing, write).await
+    }
 }
 
 pub trait AsyncInterface<Width: BusWidth>: AsyncBus<Width> {
     async fn backlight(&mut self, enable: bool) -> Result<(), Self::Error>;
 }
 
+pub trait AsyncRgbBacklight: ErrorType {
+    async fn set_color(&mut self, red: u8, green: u8, blue: u8) -> Result<(), Self::Error>;
+    async fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error>;
+}
+
 pub trait Async4BitBus: ErrorType + embedded_hal_async::delay::DelayNs {
     async fn write_nibble(&mut self, data: u8) -> Result<(), Self::Error>;
     async fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error>;
 
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &Timing::HD44780
+    }
+
     #[inline]
     async fn write_nibble_logged(&mut self, data: u8) -> Result<(), Self::Error> {
         #[cfg(feature = "log")]
@@ -139,23 +396,58 @@ pub trait Async4BitBus: ErrorType + embedded_hal_async::delay::DelayNs {
         log::debug!("Writing '{:#010b}' to LCD Display", data);
         // Set command / data mode
         self.set_command_mode(command).await?;
+        self.delay_ns(self.timing().setup_ns).await;
         // Write the data in two nibbles in MSB first order.
         self.write_nibble_logged(data >> 4).await?;
         self.write_nibble_logged(data).await?;
         Ok(())
     }
 
+    async fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        let write = async |bus: &mut Self, data, command| {
+            <Self as Async4BitBus>::write(bus, data, command).await
+        };
+        write_paced_async(self, byte_commands(data, command), timing, write).await
+    }
+
+    async fn write_many(
+        &mut self,
+        commands: &[Command],
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        let write = async |bus: &mut Self, data, command| {
+            <Self as Async4BitBus>::write(bus, data, command).await
+        };
+        write_paced_async(self, commands.iter().copied(), timing, write).await
+    }
+
     async fn initialize(&mut self) -> Result<(), Self::Error> {
+        self.set_command_mode(true).await?;
+        self.delay_ns(self.timing().setup_ns).await;
+        // Wake up in 8-bit mode, then switch to 4-bit mode
         self.write_nibble_logged(0b0011).await?;
-        self.delay_us(4500).await;
+        self.delay_us(self.timing().init_long_us).await;
         self.write_nibble_logged(0b0011).await?;
-        self.delay_us(150).await;
+        self.delay_us(self.timing().init_short_us).await;
         self.write_nibble_logged(0b0011).await?;
-        self.write_nibble_logged(0b0010).await
+        self.delay_us(self.timing().execution_us).await;
+        self.write_nibble_logged(0b0010).await?;
+        self.delay_us(self.timing().execution_us).await;
+        Ok(())
     }
 }
 
 impl<T: Async4BitBus> AsyncBus<FourBitBus> for T {
+    #[inline]
+    fn timing(&self) -> &Timing {
+        Async4BitBus::timing(self)
+    }
+
     async fn initialize(&mut self) -> Result<(), Self::Error> {
         Async4BitBus::initialize(self).await
     }
@@ -163,30 +455,86 @@ impl<T: Async4BitBus> AsyncBus<FourBitBus> for T {
     async fn write(&mut self, data: u8, command: bool) -> Result<(), Self::Error> {
         Async4BitBus::write(self, data, command).await
     }
+
+    #[inline]
+    async fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        Async4BitBus::write_bytes(self, data, command, timing).await
+    }
+
+    #[inline]
+    async fn write_many(
+        &mut self,
+        commands: &[Command],
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        Async4BitBus::write_many(self, commands, timing).await
+    }
 }
 pub trait Async8BitBus: ErrorType + embedded_hal_async::delay::DelayNs {
     async fn write_byte(&mut self, data: u8) -> Result<(), Self::Error>;
 
     async fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error>;
 
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &Timing::HD44780
+    }
+
     async fn write(&mut self, data: u8, command: bool) -> Result<(), Self::Error> {
         #[cfg(feature = "log")]
         log::debug!("Writing '{:#010b}' to LCD Display", data);
         // Set command / data mode
         self.set_command_mode(command).await?;
+        self.delay_ns(self.timing().setup_ns).await;
+
         // Write the data
         self.write_byte(data).await
     }
 
+    async fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        let write = async |bus: &mut Self, data, command| {
+            <Self as Async8BitBus>::write(bus, data, command).await
+        };
+        write_paced_async(self, byte_commands(data, command), timing, write).await
+    }
+
+    async fn write_many(
+        &mut self,
+        commands: &[Command],
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        let write = async |bus: &mut Self, data, command| {
+            <Self as Async8BitBus>::write(bus, data, command).await
+        };
+        write_paced_async(self, commands.iter().copied(), timing, write).await
+    }
+
     async fn initialize(&mut self) -> Result<(), Self::Error> {
-        self.write_byte(0b0011_0000).await?;
-        self.delay_us(4500).await;
-        self.write_byte(0b0011_0000).await?;
-        self.delay_us(150).await;
-        self.write_byte(0b0011_0000).await
+        self.write(0b0011_0000, true).await?;
+        self.delay_us(self.timing().init_long_us).await;
+        self.write(0b0011_0000, true).await?;
+        self.delay_us(self.timing().init_short_us).await;
+        self.write(0b0011_0000, true).await?;
+        self.delay_us(self.timing().execution_us).await;
+        Ok(())
     }
 }
 impl<T: Async8BitBus> AsyncBus<EightBitBus> for T {
+    #[inline]
+    fn timing(&self) -> &Timing {
+        Async8BitBus::timing(self)
+    }
+
     #[inline]
     async fn initialize(&mut self) -> Result<(), Self::Error> {
         Async8BitBus::initialize(self).await
@@ -196,13 +544,52 @@ impl<T: Async8BitBus> AsyncBus<EightBitBus> for T {
     async fn write(&mut self, data: u8, command: bool) -> Result<(), Self::Error> {
         Async8BitBus::write(self, data, command).await
     }
+
+    #[inline]
+    async fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        Async8BitBus::write_bytes(self, data, command, timing).await
+    }
+
+    #[inline]
+    async fn write_many(
+        &mut self,
+        commands: &[Command],
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        Async8BitBus::write_many(self, commands, timing).await
+    }
 }
 
 // Re-exports
+mod adapter;
+#[cfg(feature = "io")]
+mod backpack;
+mod grove_rgb;
 mod i2c;
+mod native_i2c;
 mod parallel_eight_bits;
 mod parallel_four_bits;
+mod retry;
+mod serial;
+mod shift_register;
+mod timeout;
+mod two_wire;
 
+pub use adapter::{AsyncToBlocking, BlockOn, BlockingToAsync, SpinBlockOn};
+#[cfg(feature = "io")]
+pub use backpack::{Backpack, BackpackProtocol};
+pub use grove_rgb::GroveRgbLcd;
 pub use i2c::I2c;
+pub use native_i2c::NativeI2c;
 pub use parallel_eight_bits::*;
 pub use parallel_four_bits::*;
+pub use retry::RetryPolicy;
+pub use serial::*;
+pub use shift_register::*;
+pub use timeout::{Timeout, TimeoutError};
+pub use two_wire::*;
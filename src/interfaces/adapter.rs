@@ -0,0 +1,263 @@
+use crate::interfaces::{
+    write_paced_async, AsyncBus, AsyncInterface, AsyncRgbBacklight, BlockingBus, BlockingInterface,
+    BlockingRgbBacklight, BusWidth, ErrorKind, ErrorType,
+};
+use crate::{Command, Timing};
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+/// Runs a future to completion from blocking code.
+///
+/// Implement it with the `block_on` of the executor in use, e.g. `embassy_futures::block_on`.
+pub trait BlockOn {
+    fn block_on<F: Future>(&mut self, future: F) -> F::Output;
+}
+
+/// Polls the future in a busy loop until it completes.
+///
+/// Only suitable for futures which make progress on every poll, as they are never woken.
+#[derive(Default, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct SpinBlockOn;
+
+impl BlockOn for SpinBlockOn {
+    fn block_on<F: Future>(&mut self, future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut context = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+}
+
+/// Drives a blocking interface from async code.
+///
+/// The waits for the execution of the instructions are forwarded to the async delay, so other
+/// tasks can run in the meantime. The E pulses and the initialization by instruction still use
+/// the delay of the wrapped interface, and so does `write_bytes`. It is forwarded as a whole to
+/// keep the transfers of the wrapped interface, which blocks the executor for the waits between
+/// the bytes.
+#[derive(Debug)]
+pub struct BlockingToAsync<T, DELAY> {
+    interface: T,
+    delay: DELAY,
+}
+
+impl<T, DELAY> BlockingToAsync<T, DELAY>
+where
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    pub fn new(interface: T, delay: DELAY) -> Self {
+        Self { interface, delay }
+    }
+
+    #[inline]
+    pub fn release(self) -> (T, DELAY) {
+        (self.interface, self.delay)
+    }
+}
+
+impl<T: ErrorType, DELAY> ErrorType for BlockingToAsync<T, DELAY> {
+    type Error = T::Error;
+
+    #[inline]
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        T::error_kind(error)
+    }
+}
+
+impl<T, DELAY> embedded_hal_async::delay::DelayNs for BlockingToAsync<T, DELAY>
+where
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    async fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns).await;
+    }
+}
+
+impl<W, T, DELAY> AsyncBus<W> for BlockingToAsync<T, DELAY>
+where
+    W: BusWidth,
+    T: BlockingBus<W>,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    fn timing(&self) -> &Timing {
+        self.interface.timing()
+    }
+
+    #[inline]
+    async fn initialize(&mut self) -> Result<(), Self::Error> {
+        self.interface.initialize()
+    }
+
+    #[inline]
+    async fn write(&mut self, data: u8, command: bool) -> Result<(), Self::Error> {
+        self.interface.write(data, command)
+    }
+
+    // Blocks on the delay of the wrapped interface between the bytes
+    #[inline]
+    async fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        self.interface.write_bytes(data, command, timing)
+    }
+
+    // The waits between the commands go to the async delay
+    async fn write_many(
+        &mut self,
+        commands: &[Command],
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        let write = async |bus: &mut Self, data, command| bus.interface.write(data, command);
+        write_paced_async(self, commands.iter().copied(), timing, write).await
+    }
+}
+
+impl<W, T, DELAY> AsyncInterface<W> for BlockingToAsync<T, DELAY>
+where
+    W: BusWidth,
+    T: BlockingInterface<W>,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    async fn backlight(&mut self, enable: bool) -> Result<(), Self::Error> {
+        self.interface.backlight(enable)
+    }
+}
+
+impl<T, DELAY> AsyncRgbBacklight for BlockingToAsync<T, DELAY>
+where
+    T: BlockingRgbBacklight,
+{
+    #[inline]
+    async fn set_color(&mut self, red: u8, green: u8, blue: u8) -> Result<(), Self::Error> {
+        self.interface.set_color(red, green, blue)
+    }
+
+    #[inline]
+    async fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error> {
+        self.interface.set_brightness(brightness)
+    }
+}
+
+/// Drives an async interface from blocking code.
+///
+/// Every transfer and delay of the wrapped interface is run to completion by `block_on`.
+#[derive(Debug)]
+pub struct AsyncToBlocking<T, B> {
+    interface: T,
+    block_on: B,
+}
+
+impl<T, B: BlockOn> AsyncToBlocking<T, B> {
+    #[inline]
+    pub fn new(interface: T, block_on: B) -> Self {
+        Self {
+            interface,
+            block_on,
+        }
+    }
+
+    #[inline]
+    pub fn release(self) -> (T, B) {
+        (self.interface, self.block_on)
+    }
+}
+
+impl<T: ErrorType, B> ErrorType for AsyncToBlocking<T, B> {
+    type Error = T::Error;
+
+    #[inline]
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        T::error_kind(error)
+    }
+}
+
+impl<T, B> embedded_hal::delay::DelayNs for AsyncToBlocking<T, B>
+where
+    T: embedded_hal_async::delay::DelayNs,
+    B: BlockOn,
+{
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        self.block_on.block_on(self.interface.delay_ns(ns));
+    }
+}
+
+impl<W, T, B> BlockingBus<W> for AsyncToBlocking<T, B>
+where
+    W: BusWidth,
+    T: AsyncBus<W>,
+    B: BlockOn,
+{
+    #[inline]
+    fn timing(&self) -> &Timing {
+        self.interface.timing()
+    }
+
+    #[inline]
+    fn initialize(&mut self) -> Result<(), Self::Error> {
+        self.block_on.block_on(self.interface.initialize())
+    }
+
+    #[inline]
+    fn write(&mut self, data: u8, command: bool) -> Result<(), Self::Error> {
+        self.block_on.block_on(self.interface.write(data, command))
+    }
+
+    #[inline]
+    fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        self.block_on
+            .block_on(self.interface.write_bytes(data, command, timing))
+    }
+
+    #[inline]
+    fn write_many(&mut self, commands: &[Command], timing: &Timing) -> Result<(), Self::Error> {
+        self.block_on
+            .block_on(self.interface.write_many(commands, timing))
+    }
+}
+
+impl<W, T, B> BlockingInterface<W> for AsyncToBlocking<T, B>
+where
+    W: BusWidth,
+    T: AsyncInterface<W>,
+    B: BlockOn,
+{
+    #[inline]
+    fn backlight(&mut self, enable: bool) -> Result<(), Self::Error> {
+        self.block_on.block_on(self.interface.backlight(enable))
+    }
+}
+
+impl<T, B> BlockingRgbBacklight for AsyncToBlocking<T, B>
+where
+    T: AsyncRgbBacklight,
+    B: BlockOn,
+{
+    #[inline]
+    fn set_color(&mut self, red: u8, green: u8, blue: u8) -> Result<(), Self::Error> {
+        self.block_on
+            .block_on(self.interface.set_color(red, green, blue))
+    }
+
+    #[inline]
+    fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error> {
+        self.block_on
+            .block_on(self.interface.set_brightness(brightness))
+    }
+}
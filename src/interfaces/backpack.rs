@@ -0,0 +1,440 @@
+use crate::controllers::{Controller, Hd44780};
+use crate::interfaces::{
+    Async8BitBus, AsyncInterface, AsyncRgbBacklight, Blocking8BitBus, BlockingInterface,
+    BlockingRgbBacklight, EightBitBus, ErrorKind, ErrorType,
+};
+use crate::matrix_orbital as mo;
+use crate::{Async, Blocking, Lines, Mode};
+use core::marker::PhantomData;
+
+// HD44780 instructions as sent by the display
+const CLEAR: u8 = 0b0000_0001;
+const HOME: u8 = 0b0000_0010;
+const ENTRY_MODE_SET: u8 = 0b0000_0100;
+const DISPLAY_CONTROL: u8 = 0b0000_1000;
+const SHIFT: u8 = 0b0001_0000;
+const FUNCTION_SET: u8 = 0b0010_0000;
+const SET_CGRAM_ADDRESS: u8 = 0b0100_0000;
+const SET_DDRAM_ADDRESS: u8 = 0b1000_0000;
+
+const CURSOR: u8 = 0b0000_0010;
+const BLINK: u8 = 0b0000_0001;
+const SHIFT_DISPLAY: u8 = 0b0000_1000;
+const SHIFT_RIGHT: u8 = 0b0000_0100;
+
+// Prefix of the commands passed on to the LCD
+const COMMAND: u8 = 0xFE;
+
+// SparkFun SerLCD (OpenLCD firmware)
+const SETTING: u8 = b'|';
+const SERLCD_CUSTOM_CHAR: u8 = 27;
+const SERLCD_RGB: u8 = b'+';
+
+// Characters the backpacks would interpret as the start of a command
+const REPLACEMENT_CHARACTER: u8 = b'?';
+
+const FRAME_SIZE: usize = 11;
+
+const LINES: [Lines; 4] = [Lines::_1, Lines::_2, Lines::_3, Lines::_4];
+
+/// Command set of the serial LCD backpack.
+#[derive(Default, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum BackpackProtocol {
+    /// SparkFun SerLCD / OpenLCD, which passes HD44780 instructions on after a `0xFE` prefix
+    #[default]
+    SerLcd,
+    /// Matrix Orbital compatible backpacks with their own `0xFE` prefixed command set
+    MatrixOrbital,
+}
+
+// Bytes to send to the backpack for a single instruction or character
+#[derive(Debug)]
+struct Frame {
+    buffer: [u8; FRAME_SIZE],
+    len: usize,
+}
+
+impl Frame {
+    #[inline]
+    fn new(data: &[u8]) -> Self {
+        let mut buffer = [0; FRAME_SIZE];
+        buffer[..data.len()].copy_from_slice(data);
+        Self {
+            buffer,
+            len: data.len(),
+        }
+    }
+
+    #[inline]
+    fn empty() -> Self {
+        Self::new(&[])
+    }
+
+    #[inline]
+    fn bytes(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+}
+
+/// Interface for "smart" LCD backpacks connected via UART or another byte stream.
+///
+/// The HD44780 instructions of the display are translated into the command set of the backpack.
+/// Instructions without an equivalent, e.g. the function set, are dropped, as the backpack
+/// initializes the LCD on its own. Custom characters are collected from the CGRAM writes and sent
+/// as a whole.
+#[derive(Debug)]
+pub struct Backpack<T, DELAY, M: Mode> {
+    io: T,
+    delay: DELAY,
+    protocol: BackpackProtocol,
+    command: bool,
+    // Custom character and row addressed by the last CGRAM write
+    cgram: Option<(u8, u8)>,
+    bitmap: [u8; 8],
+    display_control: u8,
+    color: [u8; 3],
+    brightness: u8,
+    // DDRAM addresses of the first character of each line in use
+    rows: [u8; 4],
+    lines: usize,
+    _mode: PhantomData<M>,
+}
+
+impl<T, DELAY, M: Mode> Backpack<T, DELAY, M> {
+    #[inline]
+    pub fn with_protocol(mut self, protocol: BackpackProtocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    // Layout of the display, which the cursor positions of the Matrix Orbital protocol are
+    // derived from. Defaults to a HD44780 with four lines of 20 characters.
+    pub fn with_layout<C: Controller>(mut self, controller: &C, lines: Lines) -> Self {
+        self.rows = LINES.map(|line| controller.row_offset(lines, line));
+        self.lines = LINES.iter().position(|l| *l == lines).unwrap_or_default() + 1;
+        self
+    }
+
+    #[inline]
+    fn create(io: T, delay: DELAY) -> Self {
+        let controller = Hd44780::default();
+        Self {
+            io,
+            delay,
+            protocol: BackpackProtocol::default(),
+            command: true,
+            cgram: None,
+            bitmap: [0; 8],
+            display_control: DISPLAY_CONTROL,
+            color: [0xFF; 3],
+            brightness: 0xFF,
+            rows: LINES.map(|line| controller.row_offset(Lines::_4, line)),
+            lines: LINES.len(),
+            _mode: PhantomData,
+        }
+    }
+
+    fn translate(&mut self, data: u8, command: bool) -> Frame {
+        match command {
+            true => self.translate_command(data),
+            false => self.translate_data(data),
+        }
+    }
+
+    fn translate_command(&mut self, command: u8) -> Frame {
+        self.cgram = None;
+        if command >= SET_DDRAM_ADDRESS {
+            return match self.protocol {
+                BackpackProtocol::SerLcd => Frame::new(&[COMMAND, command]),
+                BackpackProtocol::MatrixOrbital => {
+                    let (column, row) = self.position(command & !SET_DDRAM_ADDRESS);
+                    Frame::new(&[COMMAND, mo::GOTO, column + 1, row + 1])
+                }
+            };
+        }
+        if command >= SET_CGRAM_ADDRESS {
+            let address = command & !SET_CGRAM_ADDRESS;
+            self.cgram = Some((address >> 3, address & 0b0000_0111));
+            return Frame::empty();
+        }
+        if command >= FUNCTION_SET {
+            // The backpack configures the LCD on its own
+            return Frame::empty();
+        }
+        match self.protocol {
+            BackpackProtocol::SerLcd => Frame::new(&[COMMAND, command]),
+            BackpackProtocol::MatrixOrbital => self.translate_matrix_orbital(command),
+        }
+    }
+
+    fn translate_matrix_orbital(&mut self, command: u8) -> Frame {
+        if command >= SHIFT {
+            return match (command & SHIFT_DISPLAY, command & SHIFT_RIGHT) {
+                (0, 0) => Frame::new(&[COMMAND, mo::CURSOR_LEFT]),
+                (0, _) => Frame::new(&[COMMAND, mo::CURSOR_RIGHT]),
+                // Shifting the display is not supported
+                _ => Frame::empty(),
+            };
+        }
+        if command >= DISPLAY_CONTROL {
+            let changed = self.display_control ^ command;
+            self.display_control = command;
+            let mut frame = Frame::empty();
+            if changed & CURSOR != 0 {
+                frame = match command & CURSOR {
+                    0 => Frame::new(&[COMMAND, mo::UNDERLINE_OFF]),
+                    _ => Frame::new(&[COMMAND, mo::UNDERLINE_ON]),
+                };
+            }
+            if changed & BLINK != 0 {
+                let blink = match command & BLINK {
+                    0 => mo::BLINK_OFF,
+                    _ => mo::BLINK_ON,
+                };
+                frame.buffer[frame.len] = COMMAND;
+                frame.buffer[frame.len + 1] = blink;
+                frame.len += 2;
+            }
+            return frame;
+        }
+        match command {
+            CLEAR => Frame::new(&[COMMAND, mo::CLEAR]),
+            HOME..ENTRY_MODE_SET => Frame::new(&[COMMAND, mo::HOME]),
+            // The entry mode is fixed to left to right
+            _ => Frame::empty(),
+        }
+    }
+
+    fn translate_data(&mut self, data: u8) -> Frame {
+        let Some((location, row)) = self.cgram else {
+            return match (self.protocol, data) {
+                (_, COMMAND) | (BackpackProtocol::SerLcd, SETTING) => {
+                    Frame::new(&[REPLACEMENT_CHARACTER])
+                }
+                _ => Frame::new(&[data]),
+            };
+        };
+        self.bitmap[row as usize] = data;
+        if row + 1 < self.bitmap.len() as u8 {
+            self.cgram = Some((location, row + 1));
+            return Frame::empty();
+        }
+        // The bitmap is complete, continue with the next character like the CGRAM does
+        self.cgram = Some(((location + 1) & 0b0000_0111, 0));
+        let mut frame = match self.protocol {
+            BackpackProtocol::SerLcd => Frame::new(&[SETTING, SERLCD_CUSTOM_CHAR + location]),
+            BackpackProtocol::MatrixOrbital => Frame::new(&[COMMAND, mo::CUSTOM_CHAR, location]),
+        };
+        frame.buffer[frame.len..frame.len + 8].copy_from_slice(&self.bitmap);
+        frame.len += 8;
+        frame
+    }
+
+    fn backlight_frame(&self, enable: bool) -> Frame {
+        match (self.protocol, enable) {
+            (BackpackProtocol::SerLcd, true) => self.color_frame(),
+            (BackpackProtocol::SerLcd, false) => Frame::new(&[SETTING, SERLCD_RGB, 0, 0, 0]),
+            (BackpackProtocol::MatrixOrbital, true) => Frame::new(&[COMMAND, mo::BACKLIGHT_ON, 0]),
+            (BackpackProtocol::MatrixOrbital, false) => Frame::new(&[COMMAND, mo::BACKLIGHT_OFF]),
+        }
+    }
+
+    fn color_frame(&self) -> Frame {
+        match self.protocol {
+            BackpackProtocol::SerLcd => {
+                // OpenLCD has no separate brightness, the color is dimmed instead
+                let [red, green, blue] = self
+                    .color
+                    .map(|c| (c as u16 * self.brightness as u16 / 0xFF) as u8);
+                Frame::new(&[SETTING, SERLCD_RGB, red, green, blue])
+            }
+            BackpackProtocol::MatrixOrbital => {
+                let [red, green, blue] = self.color;
+                Frame::new(&[COMMAND, mo::RGB, red, green, blue])
+            }
+        }
+    }
+
+    fn brightness_frame(&self) -> Frame {
+        match self.protocol {
+            BackpackProtocol::SerLcd => self.color_frame(),
+            BackpackProtocol::MatrixOrbital => {
+                Frame::new(&[COMMAND, mo::BRIGHTNESS, self.brightness])
+            }
+        }
+    }
+
+    // Column and row of a DDRAM address, on the line starting closest before it
+    fn position(&self, address: u8) -> (u8, u8) {
+        let (row, offset) = self.rows[..self.lines]
+            .iter()
+            .enumerate()
+            .filter(|(_, offset)| **offset <= address)
+            .max_by_key(|(_, offset)| **offset)
+            .map_or((0, 0), |(row, offset)| (row as u8, *offset));
+        (address - offset, row)
+    }
+}
+
+impl<T, DELAY, M: Mode> ErrorType for Backpack<T, DELAY, M>
+where
+    T: embedded_io::ErrorType,
+{
+    type Error = T::Error;
+
+    #[inline]
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        ErrorKind::Io(embedded_io::Error::kind(error))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// BLOCKING INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<T, DELAY> Backpack<T, DELAY, Blocking>
+where
+    T: embedded_io::Write,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    pub fn new(io: T, delay: DELAY) -> Self {
+        Self::create(io, delay)
+    }
+}
+
+impl<T, DELAY> embedded_hal::delay::DelayNs for Backpack<T, DELAY, Blocking>
+where
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns);
+    }
+}
+
+impl<T, DELAY> Blocking8BitBus for Backpack<T, DELAY, Blocking>
+where
+    T: embedded_io::Write,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    fn write_byte(&mut self, data: u8) -> Result<(), Self::Error> {
+        let frame = self.translate(data, self.command);
+        self.io.write_all(frame.bytes())
+    }
+
+    #[inline]
+    fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        self.command = command;
+        Ok(())
+    }
+
+    #[inline]
+    fn initialize(&mut self) -> Result<(), Self::Error> {
+        // The backpack initializes the LCD on its own
+        Ok(())
+    }
+}
+
+impl<T, DELAY> BlockingInterface<EightBitBus> for Backpack<T, DELAY, Blocking>
+where
+    T: embedded_io::Write,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    fn backlight(&mut self, enable: bool) -> Result<(), Self::Error> {
+        self.io.write_all(self.backlight_frame(enable).bytes())
+    }
+}
+
+impl<T, DELAY> BlockingRgbBacklight for Backpack<T, DELAY, Blocking>
+where
+    T: embedded_io::Write,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    fn set_color(&mut self, red: u8, green: u8, blue: u8) -> Result<(), Self::Error> {
+        self.color = [red, green, blue];
+        self.io.write_all(self.color_frame().bytes())
+    }
+
+    fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error> {
+        self.brightness = brightness;
+        self.io.write_all(self.brightness_frame().bytes())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// ASYNC INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<T, DELAY> Backpack<T, DELAY, Async>
+where
+    T: embedded_io_async::Write,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    pub fn new_async(io: T, delay: DELAY) -> Self {
+        Self::create(io, delay)
+    }
+}
+
+impl<T, DELAY> embedded_hal_async::delay::DelayNs for Backpack<T, DELAY, Async>
+where
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    async fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns).await;
+    }
+}
+
+impl<T, DELAY> Async8BitBus for Backpack<T, DELAY, Async>
+where
+    T: embedded_io_async::Write,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    async fn write_byte(&mut self, data: u8) -> Result<(), Self::Error> {
+        let frame = self.translate(data, self.command);
+        self.io.write_all(frame.bytes()).await
+    }
+
+    #[inline]
+    async fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        self.command = command;
+        Ok(())
+    }
+
+    #[inline]
+    async fn initialize(&mut self) -> Result<(), Self::Error> {
+        // The backpack initializes the LCD on its own
+        Ok(())
+    }
+}
+
+impl<T, DELAY> AsyncInterface<EightBitBus> for Backpack<T, DELAY, Async>
+where
+    T: embedded_io_async::Write,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    async fn backlight(&mut self, enable: bool) -> Result<(), Self::Error> {
+        let frame = self.backlight_frame(enable);
+        self.io.write_all(frame.bytes()).await
+    }
+}
+
+impl<T, DELAY> AsyncRgbBacklight for Backpack<T, DELAY, Async>
+where
+    T: embedded_io_async::Write,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    async fn set_color(&mut self, red: u8, green: u8, blue: u8) -> Result<(), Self::Error> {
+        self.color = [red, green, blue];
+        let frame = self.color_frame();
+        self.io.write_all(frame.bytes()).await
+    }
+
+    async fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error> {
+        self.brightness = brightness;
+        let frame = self.brightness_frame();
+        self.io.write_all(frame.bytes()).await
+    }
+}
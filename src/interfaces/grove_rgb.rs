@@ -0,0 +1,304 @@
+use crate::interfaces::{
+    Async8BitBus, AsyncInterface, AsyncRgbBacklight, Blocking8BitBus, BlockingInterface,
+    BlockingRgbBacklight, EightBitBus, ErrorKind, ErrorType, NativeI2c,
+};
+use crate::{Async, Blocking, Command, Mode, Timing};
+use core::fmt::Debug;
+use embedded_hal::i2c::SevenBitAddress;
+
+const LCD_ADDRESS: SevenBitAddress = 0x3E;
+// Address of the PCA9633 on most boards, the RGB controller answers elsewhere on some board
+// revisions (0x30 on some v5 boards), see `with_rgb_address`
+const RGB_ADDRESS: SevenBitAddress = 0x62;
+
+// PCA9633 registers
+const MODE1: u8 = 0x00;
+const MODE2: u8 = 0x01;
+const PWM_BLUE: u8 = 0x02;
+const PWM_GREEN: u8 = 0x03;
+const PWM_RED: u8 = 0x04;
+const GROUP_PWM: u8 = 0x06;
+const LED_OUTPUT: u8 = 0x08;
+
+// All outputs controlled by their individual and the group PWM
+const LEDS_ON: u8 = 0xFF;
+const LEDS_OFF: u8 = 0x00;
+// Oscillator start-up time after leaving sleep mode
+const OSCILLATOR_STARTUP_US: u32 = 500;
+
+#[derive(Debug)]
+pub struct GroveRgbLcd<'a, I2C, DELAY, M: Mode> {
+    lcd: NativeI2c<'a, I2C, SevenBitAddress, DELAY, M>,
+    rgb_address: SevenBitAddress,
+    color: [u8; 3],
+    brightness: u8,
+}
+
+impl<I2C, DELAY, M: Mode> GroveRgbLcd<'_, I2C, DELAY, M> {
+    // Sets the I2C address of the RGB controller for board revisions not answering at the default
+    #[inline]
+    pub fn with_rgb_address(mut self, address: SevenBitAddress) -> Self {
+        self.rgb_address = address;
+        self
+    }
+
+    #[inline]
+    pub fn with_color(mut self, red: u8, green: u8, blue: u8) -> Self {
+        self.color = [red, green, blue];
+        self
+    }
+
+    #[inline]
+    pub fn with_brightness(mut self, brightness: u8) -> Self {
+        self.brightness = brightness;
+        self
+    }
+
+    #[inline]
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        self.lcd = self.lcd.with_timing(timing);
+        self
+    }
+}
+
+impl<I2C, DELAY, M: Mode> ErrorType for GroveRgbLcd<'_, I2C, DELAY, M>
+where
+    I2C: embedded_hal::i2c::ErrorType,
+{
+    type Error = I2C::Error;
+
+    #[inline]
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        ErrorKind::I2c(embedded_hal::i2c::Error::kind(error))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// BLOCKING INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<'a, I2C, DELAY> GroveRgbLcd<'a, I2C, DELAY, Blocking>
+where
+    I2C: embedded_hal::i2c::I2c,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    pub fn new(i2c: &'a mut I2C, delay: DELAY) -> Self {
+        Self {
+            lcd: NativeI2c::new(i2c, LCD_ADDRESS, delay),
+            rgb_address: RGB_ADDRESS,
+            color: [0xFF; 3],
+            brightness: 0xFF,
+        }
+    }
+
+    #[inline]
+    fn write_register(&mut self, register: u8, value: u8) -> Result<(), I2C::Error> {
+        let address = self.rgb_address;
+        self.lcd.bus().write(address, &[register, value])
+    }
+}
+
+impl<I2C, DELAY> embedded_hal::delay::DelayNs for GroveRgbLcd<'_, I2C, DELAY, Blocking>
+where
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        embedded_hal::delay::DelayNs::delay_ns(&mut self.lcd, ns);
+    }
+}
+
+impl<I2C, DELAY> Blocking8BitBus for GroveRgbLcd<'_, I2C, DELAY, Blocking>
+where
+    I2C: embedded_hal::i2c::I2c,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    fn timing(&self) -> &Timing {
+        Blocking8BitBus::timing(&self.lcd)
+    }
+
+    #[inline]
+    fn write_byte(&mut self, data: u8) -> Result<(), Self::Error> {
+        self.lcd.write_byte(data)
+    }
+
+    #[inline]
+    fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        Blocking8BitBus::set_command_mode(&mut self.lcd, command)
+    }
+
+    #[inline]
+    fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        Blocking8BitBus::write_bytes(&mut self.lcd, data, command, timing)
+    }
+
+    #[inline]
+    fn write_many(&mut self, commands: &[Command], timing: &Timing) -> Result<(), Self::Error> {
+        Blocking8BitBus::write_many(&mut self.lcd, commands, timing)
+    }
+
+    fn initialize(&mut self) -> Result<(), Self::Error> {
+        Blocking8BitBus::initialize(&mut self.lcd)?;
+        // Wake up the RGB controller and use the group PWM for dimming
+        self.write_register(MODE1, 0)?;
+        embedded_hal::delay::DelayNs::delay_us(&mut self.lcd, OSCILLATOR_STARTUP_US);
+        self.write_register(MODE2, 0)?;
+        let [red, green, blue] = self.color;
+        self.set_color(red, green, blue)?;
+        self.set_brightness(self.brightness)
+    }
+}
+
+impl<I2C, DELAY> BlockingInterface<EightBitBus> for GroveRgbLcd<'_, I2C, DELAY, Blocking>
+where
+    I2C: embedded_hal::i2c::I2c,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    fn backlight(&mut self, enable: bool) -> Result<(), Self::Error> {
+        match enable {
+            true => self.write_register(LED_OUTPUT, LEDS_ON),
+            false => self.write_register(LED_OUTPUT, LEDS_OFF),
+        }
+    }
+}
+
+impl<I2C, DELAY> BlockingRgbBacklight for GroveRgbLcd<'_, I2C, DELAY, Blocking>
+where
+    I2C: embedded_hal::i2c::I2c,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    fn set_color(&mut self, red: u8, green: u8, blue: u8) -> Result<(), Self::Error> {
+        self.color = [red, green, blue];
+        self.write_register(PWM_RED, red)?;
+        self.write_register(PWM_GREEN, green)?;
+        self.write_register(PWM_BLUE, blue)
+    }
+
+    fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error> {
+        self.brightness = brightness;
+        self.write_register(GROUP_PWM, brightness)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// ASYNC INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<'a, I2C, DELAY> GroveRgbLcd<'a, I2C, DELAY, Async>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    pub fn new_async(i2c: &'a mut I2C, delay: DELAY) -> Self {
+        Self {
+            lcd: NativeI2c::new_async(i2c, LCD_ADDRESS, delay),
+            rgb_address: RGB_ADDRESS,
+            color: [0xFF; 3],
+            brightness: 0xFF,
+        }
+    }
+
+    #[inline]
+    async fn write_register(&mut self, register: u8, value: u8) -> Result<(), I2C::Error> {
+        let address = self.rgb_address;
+        self.lcd.bus().write(address, &[register, value]).await
+    }
+}
+
+impl<I2C, DELAY> embedded_hal_async::delay::DelayNs for GroveRgbLcd<'_, I2C, DELAY, Async>
+where
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    async fn delay_ns(&mut self, ns: u32) {
+        embedded_hal_async::delay::DelayNs::delay_ns(&mut self.lcd, ns).await;
+    }
+}
+
+impl<I2C, DELAY> Async8BitBus for GroveRgbLcd<'_, I2C, DELAY, Async>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    fn timing(&self) -> &Timing {
+        Async8BitBus::timing(&self.lcd)
+    }
+
+    #[inline]
+    async fn write_byte(&mut self, data: u8) -> Result<(), Self::Error> {
+        self.lcd.write_byte(data).await
+    }
+
+    #[inline]
+    async fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        Async8BitBus::set_command_mode(&mut self.lcd, command).await
+    }
+
+    #[inline]
+    async fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        Async8BitBus::write_bytes(&mut self.lcd, data, command, timing).await
+    }
+
+    #[inline]
+    async fn write_many(
+        &mut self,
+        commands: &[Command],
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        Async8BitBus::write_many(&mut self.lcd, commands, timing).await
+    }
+
+    async fn initialize(&mut self) -> Result<(), Self::Error> {
+        Async8BitBus::initialize(&mut self.lcd).await?;
+        // Wake up the RGB controller and use the group PWM for dimming
+        self.write_register(MODE1, 0).await?;
+        embedded_hal_async::delay::DelayNs::delay_us(&mut self.lcd, OSCILLATOR_STARTUP_US).await;
+        self.write_register(MODE2, 0).await?;
+        let [red, green, blue] = self.color;
+        self.set_color(red, green, blue).await?;
+        self.set_brightness(self.brightness).await
+    }
+}
+
+impl<I2C, DELAY> AsyncInterface<EightBitBus> for GroveRgbLcd<'_, I2C, DELAY, Async>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    async fn backlight(&mut self, enable: bool) -> Result<(), Self::Error> {
+        match enable {
+            true => self.write_register(LED_OUTPUT, LEDS_ON).await,
+            false => self.write_register(LED_OUTPUT, LEDS_OFF).await,
+        }
+    }
+}
+
+impl<I2C, DELAY> AsyncRgbBacklight for GroveRgbLcd<'_, I2C, DELAY, Async>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    async fn set_color(&mut self, red: u8, green: u8, blue: u8) -> Result<(), Self::Error> {
+        self.color = [red, green, blue];
+        self.write_register(PWM_RED, red).await?;
+        self.write_register(PWM_GREEN, green).await?;
+        self.write_register(PWM_BLUE, blue).await
+    }
+
+    async fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error> {
+        self.brightness = brightness;
+        self.write_register(GROUP_PWM, brightness).await
+    }
+}
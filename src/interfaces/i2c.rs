@@ -1,29 +1,87 @@
 use crate::interfaces::{
-    Async4BitBus, AsyncInterface, Blocking4BitBus, BlockingInterface, ErrorType, FourBitBus,
+    Async4BitBus, AsyncInterface, Blocking4BitBus, BlockingInterface, BlockingProbe, ErrorKind,
+    ErrorType, FourBitBus, RetryPolicy,
 };
-use crate::{Async, Blocking, Mode};
+use crate::{Async, Blocking, Mode, Timing};
 use core::fmt::Debug;
 use core::marker::PhantomData;
-use embedded_hal::i2c::AddressMode;
+use embedded_hal::i2c::{AddressMode, Error};
 
 const ENABLE: u8 = 0b0000_0100;
 const DATA: u8 = 0b0000_0001;
 const BACKGROUND: u8 = 0b0000_1000;
 
+// Every level on the expander takes an I2C transaction of about 20 µs even at 1 MHz, which
+// exceeds the RS setup time and the E pulse and hold times. Waiting for them on top is wasted.
+const TIMING: Timing = Timing {
+    setup_ns: 0,
+    enable_pulse_ns: 0,
+    enable_hold_ns: 0,
+    ..Timing::HD44780
+};
+
 #[derive(Debug)]
 pub struct I2c<'a, I2C, A, DELAY, M: Mode> {
     i2c: &'a mut I2C,
     address: A,
     delay: DELAY,
     config: u8,
+    timing: Timing,
+    // Last level of RS on the expander, if known
+    command: Option<bool>,
+    retry: RetryPolicy,
+    // Number of repeated transactions
+    retries: u32,
     _mode: PhantomData<M>,
 }
 
+impl<I2C, A, DELAY, M: Mode> I2c<'_, I2C, A, DELAY, M> {
+    #[inline]
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    #[inline]
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    // Number of transactions repeated after a retryable error so far
+    #[inline]
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    #[inline]
+    pub fn reset_retries(&mut self) {
+        self.retries = 0;
+    }
+
+    // Whether the failed attempt is repeated, counting the retry
+    #[inline]
+    fn retry<E: Error>(&mut self, attempt: u8, error: &E) -> bool {
+        if !self.retry.retry(attempt, error.kind()) {
+            return false;
+        }
+        #[cfg(feature = "log")]
+        log::warn!("Retrying I2C write after {:?}", error.kind());
+        self.retries = self.retries.wrapping_add(1);
+        true
+    }
+}
+
 impl<I2C, A, DELAY, M: Mode> ErrorType for I2c<'_, I2C, A, DELAY, M>
 where
     I2C: embedded_hal::i2c::ErrorType,
 {
     type Error = I2C::Error;
+
+    #[inline]
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        ErrorKind::I2c(embedded_hal::i2c::Error::kind(error))
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -42,6 +100,10 @@ where
             address,
             delay,
             config: 0,
+            timing: TIMING,
+            command: None,
+            retry: RetryPolicy::default(),
+            retries: 0,
             _mode: PhantomData,
         }
     }
@@ -57,31 +119,80 @@ where
     }
 }
 
+impl<I2C, A, DELAY> I2c<'_, I2C, A, DELAY, Blocking>
+where
+    A: AddressMode + Clone,
+    DELAY: embedded_hal::delay::DelayNs,
+    I2C: embedded_hal::i2c::I2c<A>,
+{
+    // Writes the byte to the expander as often as the retry policy allows
+    fn transfer(&mut self, data: u8) -> Result<(), I2C::Error> {
+        let mut attempt = 1;
+        loop {
+            match self.i2c.write(self.address.clone(), &[data]) {
+                Err(e) if self.retry(attempt, &e) => {
+                    attempt += 1;
+                    self.delay.delay_us(self.retry.backoff_us);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
 impl<I2C, A, DELAY> Blocking4BitBus for I2c<'_, I2C, A, DELAY, Blocking>
 where
     A: AddressMode + Clone,
     DELAY: embedded_hal::delay::DelayNs,
     I2C: embedded_hal::i2c::I2c<A>,
 {
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
     fn write_nibble(&mut self, nibble: u8) -> Result<(), Self::Error> {
         let data = nibble << 4 | self.config;
         // Write the data and open the latch
-        self.i2c.write(self.address.clone(), &[data | ENABLE])?;
+        self.transfer(data | ENABLE)?;
         // Wait for the controller to fetch the data
-        self.delay.delay_ns(500);
+        self.delay.delay_ns(self.timing.enable_pulse_ns);
         // Close the latch again
-        self.i2c.write(self.address.clone(), &[data & !ENABLE])?;
-        self.delay.delay_ns(500);
+        self.transfer(data & !ENABLE)?;
+        self.delay.delay_ns(self.timing.enable_hold_ns);
+        Ok(())
+    }
+
+    // Sets RS once for all bytes. The four transactions of a byte take longer than the execution
+    // of the previous one, so the bytes are sent back to back.
+    fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        _timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        self.set_command_mode(command)?;
+        for b in data {
+            self.write_nibble(b >> 4)?;
+            self.write_nibble(*b)?;
+        }
         Ok(())
     }
 
     #[inline]
     fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        // Skip the transfer if RS keeps its level
+        if self.command == Some(command) {
+            return Ok(());
+        }
         match command {
             true => self.config &= !DATA,
             false => self.config |= DATA,
         }
-        self.i2c.write(self.address.clone(), &[self.config])
+        self.command = None;
+        self.transfer(self.config)?;
+        self.command = Some(command);
+        Ok(())
     }
 }
 
@@ -96,7 +207,24 @@ where
             true => self.config |= BACKGROUND,
             false => self.config &= !BACKGROUND,
         }
-        self.i2c.write(self.address.clone(), &[self.config])
+        self.transfer(self.config)
+    }
+}
+
+impl<I2C, A, DELAY> BlockingProbe for I2c<'_, I2C, A, DELAY, Blocking>
+where
+    A: AddressMode + Clone,
+    I2C: embedded_hal::i2c::I2c<A>,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    // Whether the expander acknowledges the current state. An expander which lost its supply
+    // comes back with all pins high, so the cached level of RS is dropped while it is missing.
+    fn probe(&mut self) -> bool {
+        let present = self.transfer(self.config).is_ok();
+        if !present {
+            self.command = None;
+        }
+        present
     }
 }
 
@@ -116,6 +244,10 @@ where
             address,
             config: 0,
             delay,
+            timing: TIMING,
+            command: None,
+            retry: RetryPolicy::default(),
+            retries: 0,
             _mode: PhantomData,
         }
     }
@@ -131,35 +263,80 @@ where
     }
 }
 
+impl<I2C, A, DELAY> I2c<'_, I2C, A, DELAY, Async>
+where
+    A: AddressMode + Clone,
+    DELAY: embedded_hal_async::delay::DelayNs,
+    I2C: embedded_hal_async::i2c::I2c<A>,
+{
+    // Writes the byte to the expander as often as the retry policy allows
+    async fn transfer(&mut self, data: u8) -> Result<(), I2C::Error> {
+        let mut attempt = 1;
+        loop {
+            match self.i2c.write(self.address.clone(), &[data]).await {
+                Err(e) if self.retry(attempt, &e) => {
+                    attempt += 1;
+                    self.delay.delay_us(self.retry.backoff_us).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
 impl<I2C, A, DELAY> Async4BitBus for I2c<'_, I2C, A, DELAY, Async>
 where
     A: AddressMode + Clone,
     DELAY: embedded_hal_async::delay::DelayNs,
     I2C: embedded_hal_async::i2c::I2c<A>,
 {
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
     async fn write_nibble(&mut self, nibble: u8) -> Result<(), Self::Error> {
         let data = nibble << 4 | self.config;
         // Write the data and open the latch
-        self.i2c
-            .write(self.address.clone(), &[data | ENABLE])
-            .await?;
+        self.transfer(data | ENABLE).await?;
         // Wait for the controller to fetch the data
-        self.delay.delay_ns(500).await;
+        self.delay.delay_ns(self.timing.enable_pulse_ns).await;
         // Close the latch again
-        self.i2c
-            .write(self.address.clone(), &[data & !ENABLE])
-            .await?;
-        self.delay.delay_ns(500).await;
+        self.transfer(data & !ENABLE).await?;
+        self.delay.delay_ns(self.timing.enable_hold_ns).await;
+        Ok(())
+    }
+
+    // Sets RS once for all bytes. The four transactions of a byte take longer than the execution
+    // of the previous one, so the bytes are sent back to back.
+    async fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        _timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        self.set_command_mode(command).await?;
+        for b in data {
+            self.write_nibble(b >> 4).await?;
+            self.write_nibble(*b).await?;
+        }
         Ok(())
     }
 
     #[inline]
     async fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        // Skip the transfer if RS keeps its level
+        if self.command == Some(command) {
+            return Ok(());
+        }
         match command {
             true => self.config &= !DATA,
             false => self.config |= DATA,
         }
-        self.i2c.write(self.address.clone(), &[self.config]).await
+        self.command = None;
+        self.transfer(self.config).await?;
+        self.command = Some(command);
+        Ok(())
     }
 }
 
@@ -174,6 +351,66 @@ where
             true => self.config |= BACKGROUND,
             false => self.config &= !BACKGROUND,
         }
-        self.i2c.write(self.address.clone(), &[self.config]).await
+        self.transfer(self.config).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{I2cBus, NoDelay};
+    use crate::Display;
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+
+    #[test]
+    fn rs_is_set_once_per_string() {
+        let mut bus = I2cBus::default();
+        {
+            let mut display = Display::new(I2c::new(&mut bus, 0x27, NoDelay));
+            display.write_string("Hello").unwrap();
+            display.write_byte(b'!').unwrap();
+        }
+        // One transaction for RS, then two per nibble. Switching RS for every byte took five
+        // transactions per byte.
+        assert_eq!(bus.transactions.len(), 1 + 6 * 4);
+        assert_eq!(bus.transactions[0], [DATA]);
+        // High nibble of 'H' latched by a pulse on E
+        assert_eq!(
+            bus.transactions[1..3],
+            [[0x40 | ENABLE | DATA], [0x40 | DATA]]
+        );
+    }
+
+    #[test]
+    fn retries_until_acknowledged() {
+        let mut bus = I2cBus::default();
+        let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+        bus.failures.extend([nack; 2]);
+        let mut i2c = I2c::new(&mut bus, 0x27, NoDelay).with_retry(RetryPolicy::TRANSIENT);
+        BlockingInterface::backlight(&mut i2c, true).unwrap();
+        assert_eq!(i2c.retries(), 2);
+        assert_eq!(bus.transactions, [[BACKGROUND]]);
+    }
+
+    #[test]
+    fn fails_after_the_last_attempt() {
+        let mut bus = I2cBus::default();
+        let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+        bus.failures.extend([nack; 3]);
+        let mut i2c = I2c::new(&mut bus, 0x27, NoDelay).with_retry(RetryPolicy::TRANSIENT);
+        assert_eq!(BlockingInterface::backlight(&mut i2c, true), Err(nack));
+        assert_eq!(i2c.retries(), 2);
+    }
+
+    #[test]
+    fn overrun_is_not_retried() {
+        let mut bus = I2cBus::default();
+        bus.failures.push_back(ErrorKind::Overrun);
+        let mut i2c = I2c::new(&mut bus, 0x27, NoDelay).with_retry(RetryPolicy::TRANSIENT);
+        let result = BlockingInterface::backlight(&mut i2c, true);
+        assert_eq!(result, Err(ErrorKind::Overrun));
+        assert_eq!(i2c.retries(), 0);
+        assert!(bus.transactions.is_empty());
+        assert!(bus.failures.is_empty());
     }
 }
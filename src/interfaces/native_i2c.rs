@@ -0,0 +1,532 @@
+use crate::interfaces::{
+    Async8BitBus, AsyncInterface, Blocking8BitBus, BlockingInterface, EightBitBus, ErrorKind,
+    ErrorType,
+};
+use crate::{Async, Blocking, Command, Mode, Timing};
+use core::fmt::Debug;
+use core::marker::PhantomData;
+use embedded_hal::i2c::{AddressMode, Operation};
+
+// Control byte sent in front of every instruction or data byte
+const CONTINUATION: u8 = 0b1000_0000;
+const DATA: u8 = 0b0100_0000;
+
+// Number of instructions sent within one transaction
+const CHUNK_SIZE: usize = 8;
+// I2C fast mode, the fastest clock most of these controllers support
+const FREQUENCY_HZ: u32 = 400_000;
+
+#[derive(Debug)]
+pub struct NativeI2c<'a, I2C, A, DELAY, M: Mode> {
+    i2c: &'a mut I2C,
+    address: A,
+    delay: DELAY,
+    command: bool,
+    timing: Timing,
+    frequency_hz: u32,
+    _mode: PhantomData<M>,
+}
+
+impl<I2C, A, DELAY, M: Mode> NativeI2c<'_, I2C, A, DELAY, M> {
+    #[inline]
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    // Clock of the I2C bus, which decides how many bytes may follow each other within one
+    // transaction without outrunning the controller
+    #[inline]
+    pub fn with_frequency(mut self, frequency_hz: u32) -> Self {
+        self.frequency_hz = frequency_hz.max(1);
+        self
+    }
+
+    #[inline]
+    pub(crate) fn bus(&mut self) -> &mut I2C {
+        self.i2c
+    }
+
+    #[inline]
+    fn control_byte(&self) -> u8 {
+        match self.command {
+            true => 0,
+            false => DATA,
+        }
+    }
+
+    // Whether the given number of bytes takes longer on the bus than the execution of an
+    // instruction, counting nine clock cycles per byte including the acknowledge
+    fn covers_execution(&self, bytes: u32, timing: &Timing) -> bool {
+        let ns = u64::from(bytes) * 9_000_000_000 / u64::from(self.frequency_hz);
+        ns >= u64::from(timing.execution_us) * 1000
+    }
+
+    // Commands sent as the bytes of `write_bytes`
+    fn commands(data: &[u8], command: bool) -> [Command; CHUNK_SIZE] {
+        let mut commands = [Command::Delay(0); CHUNK_SIZE];
+        for (slot, b) in commands.iter_mut().zip(data) {
+            *slot = match command {
+                true => Command::Instruction(*b),
+                false => Command::Data(*b),
+            };
+        }
+        commands
+    }
+
+    // Encodes the leading commands which only take the execution time, with a control byte in
+    // front of each. Returns the number of commands encoded into the buffer.
+    fn encode_commands(
+        commands: &[Command],
+        timing: &Timing,
+        buffer: &mut [u8; 2 * CHUNK_SIZE],
+    ) -> usize {
+        let mut count = 0;
+        for command in commands.iter().take(CHUNK_SIZE) {
+            match command.encode() {
+                Some((data, instruction)) if command.delay_us(timing) == timing.execution_us => {
+                    buffer[2 * count] = match instruction {
+                        true => CONTINUATION,
+                        false => CONTINUATION | DATA,
+                    };
+                    buffer[2 * count + 1] = data;
+                    count += 1;
+                }
+                _ => break,
+            }
+        }
+        if count > 0 {
+            buffer[2 * count - 2] &= !CONTINUATION;
+        }
+        count
+    }
+}
+
+impl<I2C, A, DELAY, M: Mode> ErrorType for NativeI2c<'_, I2C, A, DELAY, M>
+where
+    I2C: embedded_hal::i2c::ErrorType,
+{
+    type Error = I2C::Error;
+
+    #[inline]
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        ErrorKind::I2c(embedded_hal::i2c::Error::kind(error))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// BLOCKING INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<'a, I2C, A, DELAY> NativeI2c<'a, I2C, A, DELAY, Blocking>
+where
+    A: AddressMode,
+    I2C: embedded_hal::i2c::I2c<A>,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    pub fn new(i2c: &'a mut I2C, address: A, delay: DELAY) -> Self {
+        Self {
+            i2c,
+            address,
+            delay,
+            command: true,
+            timing: Timing::default(),
+            frequency_hz: FREQUENCY_HZ,
+            _mode: PhantomData,
+        }
+    }
+}
+
+impl<I2C, A, DELAY> embedded_hal::delay::DelayNs for NativeI2c<'_, I2C, A, DELAY, Blocking>
+where
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns);
+    }
+}
+
+impl<I2C, A, DELAY> NativeI2c<'_, I2C, A, DELAY, Blocking>
+where
+    A: AddressMode + Clone,
+    DELAY: embedded_hal::delay::DelayNs,
+    I2C: embedded_hal::i2c::I2c<A>,
+{
+    // Sends the commands, packing those which only take the execution time into one transaction
+    // while the bus is slow enough. Starts with the wait left of the previous command and returns
+    // the one of the last command.
+    fn write_commands(
+        &mut self,
+        commands: &[Command],
+        timing: &Timing,
+        mut wait: u32,
+    ) -> Result<u32, I2C::Error> {
+        let packed = self.covers_execution(2, timing);
+        let mut buffer = [0; 2 * CHUNK_SIZE];
+        let mut rest = commands;
+        while let Some(command) = rest.first() {
+            self.delay.delay_us(wait);
+            let count = match packed {
+                true => Self::encode_commands(rest, timing, &mut buffer),
+                false => 0,
+            };
+            if count == 0 {
+                // Commands with a longer execution time and delays go on their own
+                if let Some((data, instruction)) = command.encode() {
+                    Blocking8BitBus::write(self, data, instruction)?;
+                }
+                wait = command.delay_us(timing);
+                rest = &rest[1..];
+                continue;
+            }
+            // The transfer of each command takes longer than the execution of the previous one
+            self.i2c.write(self.address.clone(), &buffer[..2 * count])?;
+            wait = timing.execution_us;
+            rest = &rest[count..];
+        }
+        Ok(wait)
+    }
+}
+
+impl<I2C, A, DELAY> Blocking8BitBus for NativeI2c<'_, I2C, A, DELAY, Blocking>
+where
+    A: AddressMode + Clone,
+    DELAY: embedded_hal::delay::DelayNs,
+    I2C: embedded_hal::i2c::I2c<A>,
+{
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
+    #[inline]
+    fn write_byte(&mut self, data: u8) -> Result<(), Self::Error> {
+        self.i2c
+            .write(self.address.clone(), &[self.control_byte(), data])
+    }
+
+    #[inline]
+    fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        // RS is part of the control byte
+        self.command = command;
+        Ok(())
+    }
+
+    fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        #[cfg(feature = "log")]
+        log::debug!("Writing {} bytes to LCD Display", data.len());
+        self.command = command;
+        if data.is_empty() {
+            return Ok(());
+        }
+        if !command && self.covers_execution(1, timing) {
+            // Data bytes can follow a single control byte while each of them takes longer on the
+            // bus than its execution
+            return self.i2c.transaction(
+                self.address.clone(),
+                &mut [Operation::Write(&[DATA]), Operation::Write(data)],
+            );
+        }
+        let mut wait = 0;
+        for chunk in data.chunks(CHUNK_SIZE) {
+            let commands = Self::commands(chunk, command);
+            wait = self.write_commands(&commands[..chunk.len()], timing, wait)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn write_many(&mut self, commands: &[Command], timing: &Timing) -> Result<(), Self::Error> {
+        self.write_commands(commands, timing, 0).map(|_| ())
+    }
+}
+
+impl<I2C, A, DELAY> BlockingInterface<EightBitBus> for NativeI2c<'_, I2C, A, DELAY, Blocking>
+where
+    A: AddressMode + Clone,
+    I2C: embedded_hal::i2c::I2c<A>,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    fn backlight(&mut self, _enable: bool) -> Result<(), Self::Error> {
+        // The controller has no backlight control
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// ASYNC INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<'a, I2C, A, DELAY> NativeI2c<'a, I2C, A, DELAY, Async>
+where
+    A: AddressMode,
+    I2C: embedded_hal_async::i2c::I2c<A>,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    pub fn new_async(i2c: &'a mut I2C, address: A, delay: DELAY) -> Self {
+        Self {
+            i2c,
+            address,
+            delay,
+            command: true,
+            timing: Timing::default(),
+            frequency_hz: FREQUENCY_HZ,
+            _mode: PhantomData,
+        }
+    }
+}
+
+impl<I2C, A, DELAY> embedded_hal_async::delay::DelayNs for NativeI2c<'_, I2C, A, DELAY, Async>
+where
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    async fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns).await;
+    }
+}
+
+impl<I2C, A, DELAY> NativeI2c<'_, I2C, A, DELAY, Async>
+where
+    A: AddressMode + Clone,
+    DELAY: embedded_hal_async::delay::DelayNs,
+    I2C: embedded_hal_async::i2c::I2c<A>,
+{
+    // Sends the commands, packing those which only take the execution time into one transaction
+    // while the bus is slow enough. Starts with the wait left of the previous command and returns
+    // the one of the last command.
+    async fn write_commands(
+        &mut self,
+        commands: &[Command],
+        timing: &Timing,
+        mut wait: u32,
+    ) -> Result<u32, I2C::Error> {
+        let packed = self.covers_execution(2, timing);
+        let mut buffer = [0; 2 * CHUNK_SIZE];
+        let mut rest = commands;
+        while let Some(command) = rest.first() {
+            self.delay.delay_us(wait).await;
+            let count = match packed {
+                true => Self::encode_commands(rest, timing, &mut buffer),
+                false => 0,
+            };
+            if count == 0 {
+                // Commands with a longer execution time and delays go on their own
+                if let Some((data, instruction)) = command.encode() {
+                    Async8BitBus::write(self, data, instruction).await?;
+                }
+                wait = command.delay_us(timing);
+                rest = &rest[1..];
+                continue;
+            }
+            // The transfer of each command takes longer than the execution of the previous one
+            self.i2c
+                .write(self.address.clone(), &buffer[..2 * count])
+                .await?;
+            wait = timing.execution_us;
+            rest = &rest[count..];
+        }
+        Ok(wait)
+    }
+}
+
+impl<I2C, A, DELAY> Async8BitBus for NativeI2c<'_, I2C, A, DELAY, Async>
+where
+    A: AddressMode + Clone,
+    DELAY: embedded_hal_async::delay::DelayNs,
+    I2C: embedded_hal_async::i2c::I2c<A>,
+{
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
+    #[inline]
+    async fn write_byte(&mut self, data: u8) -> Result<(), Self::Error> {
+        self.i2c
+            .write(self.address.clone(), &[self.control_byte(), data])
+            .await
+    }
+
+    #[inline]
+    async fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        // RS is part of the control byte
+        self.command = command;
+        Ok(())
+    }
+
+    async fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        #[cfg(feature = "log")]
+        log::debug!("Writing {} bytes to LCD Display", data.len());
+        self.command = command;
+        if data.is_empty() {
+            return Ok(());
+        }
+        if !command && self.covers_execution(1, timing) {
+            // Data bytes can follow a single control byte while each of them takes longer on the
+            // bus than its execution
+            return self
+                .i2c
+                .transaction(
+                    self.address.clone(),
+                    &mut [Operation::Write(&[DATA]), Operation::Write(data)],
+                )
+                .await;
+        }
+        let mut wait = 0;
+        for chunk in data.chunks(CHUNK_SIZE) {
+            let commands = Self::commands(chunk, command);
+            wait = self
+                .write_commands(&commands[..chunk.len()], timing, wait)
+                .await?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    async fn write_many(
+        &mut self,
+        commands: &[Command],
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        self.write_commands(commands, timing, 0).await.map(|_| ())
+    }
+}
+
+impl<I2C, A, DELAY> AsyncInterface<EightBitBus> for NativeI2c<'_, I2C, A, DELAY, Async>
+where
+    A: AddressMode + Clone,
+    I2C: embedded_hal_async::i2c::I2c<A>,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    async fn backlight(&mut self, _enable: bool) -> Result<(), Self::Error> {
+        // The controller has no backlight control
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+    use core::convert::Infallible;
+    use embedded_hal::i2c::SevenBitAddress;
+
+    // I2C bus on a simulated time line, which records when each byte has been transferred
+    struct Wire<'a> {
+        now: &'a Cell<u64>,
+        frequency_hz: u32,
+        // Completion time in ns and value of the bytes after the control bytes
+        bytes: Vec<(u64, u8)>,
+    }
+
+    impl Wire<'_> {
+        fn transfer(&mut self, data: &[u8]) {
+            let byte_ns = 9_000_000_000 / u64::from(self.frequency_hz);
+            // The address goes first
+            self.now.set(self.now.get() + byte_ns);
+            // A control byte with the continuation bit is followed by a single byte and the next
+            // control byte, the last one by the remaining bytes
+            let mut control = true;
+            let mut last = false;
+            for b in data {
+                self.now.set(self.now.get() + byte_ns);
+                if control && !last {
+                    last = b & CONTINUATION == 0;
+                    control = false;
+                    continue;
+                }
+                self.bytes.push((self.now.get(), *b));
+                control = !last;
+            }
+        }
+    }
+
+    impl embedded_hal::i2c::ErrorType for Wire<'_> {
+        type Error = Infallible;
+    }
+
+    impl embedded_hal::i2c::I2c<SevenBitAddress> for Wire<'_> {
+        fn transaction(
+            &mut self,
+            _address: SevenBitAddress,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            let mut data = Vec::new();
+            for operation in operations {
+                if let Operation::Write(bytes) = operation {
+                    data.extend_from_slice(bytes);
+                }
+            }
+            self.transfer(&data);
+            Ok(())
+        }
+    }
+
+    struct Delay<'a>(&'a Cell<u64>);
+
+    impl embedded_hal::delay::DelayNs for Delay<'_> {
+        fn delay_ns(&mut self, ns: u32) {
+            self.0.set(self.0.get() + u64::from(ns));
+        }
+    }
+
+    // Times between the transfers of consecutive bytes
+    fn gaps(frequency_hz: u32, data: &[u8], command: bool) -> Vec<(u8, u64)> {
+        let now = Cell::new(0);
+        let mut wire = Wire {
+            now: &now,
+            frequency_hz,
+            bytes: Vec::new(),
+        };
+        NativeI2c::new(&mut wire, 0x3E, Delay(&now))
+            .with_frequency(frequency_hz)
+            .write_bytes(data, command, &Timing::HD44780)
+            .unwrap();
+        assert_eq!(wire.bytes.iter().map(|(_, b)| *b).collect::<Vec<_>>(), data);
+        wire.bytes
+            .windows(2)
+            .map(|pair| (pair[0].1, pair[1].0 - pair[0].0))
+            .collect()
+    }
+
+    #[test]
+    fn data_bytes_wait_for_the_execution() {
+        let execution_ns = u64::from(Timing::HD44780.execution_us) * 1000;
+        for frequency_hz in [100_000, 400_000, 1_000_000, 3_400_000] {
+            let gaps = gaps(frequency_hz, &[b'x'; 20], false);
+            assert!(
+                gaps.iter().all(|(_, gap)| *gap >= execution_ns),
+                "{frequency_hz} Hz"
+            );
+        }
+    }
+
+    #[test]
+    fn clear_goes_on_its_own() {
+        let clear_ns = u64::from(Timing::HD44780.clear_us) * 1000;
+        let execution_ns = u64::from(Timing::HD44780.execution_us) * 1000;
+        for frequency_hz in [100_000, 400_000, 1_000_000] {
+            let gaps = gaps(frequency_hz, &[0x0C, 0x01, 0x80, 0x06], true);
+            for (instruction, gap) in gaps {
+                match instruction {
+                    0x01 => assert!(gap >= clear_ns, "{frequency_hz} Hz"),
+                    _ => assert!(gap >= execution_ns, "{frequency_hz} Hz"),
+                }
+            }
+        }
+    }
+}
@@ -1,11 +1,12 @@
 use super::{
-    Async8BitBus, AsyncInterface, Blocking8BitBus, BlockingInterface, EightBitBus, ErrorType,
+    Async8BitBus, AsyncInterface, Blocking8BitBus, BlockingInterface, EightBitBus, ErrorKind,
+    ErrorType,
 };
 use crate::async_output_pin::AsyncOutputPin;
-use crate::{Async, Blocking, Mode};
+use crate::{Async, Blocking, Mode, Timing};
 use core::fmt::{Debug, Formatter};
 use core::marker::PhantomData;
-use embedded_hal::digital::OutputPin;
+use embedded_hal::digital::{Error, OutputPin};
 
 pub enum Parallel8BitsError<
     D0: embedded_hal::digital::ErrorType,
@@ -79,6 +80,10 @@ pub struct Parallel8Bits<D0, D1, D2, D3, D4, D5, D6, D7, E, RS, B, DELAY, M: Mod
     rs: RS,
     delay: DELAY,
     backlight: Option<B>,
+    timing: Timing,
+    // Last levels of RS and the data pins, if known
+    command: Option<bool>,
+    data: Option<u8>,
     _mode: PhantomData<M>,
 }
 
@@ -89,6 +94,12 @@ impl<D0, D1, D2, D3, D4, D5, D6, D7, E, RS, B, DELAY, M: Mode>
         self.backlight = Some(backlight);
         self
     }
+
+    #[inline]
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
 }
 
 impl<D0, D1, D2, D3, D4, D5, D6, D7, E, RS, B, DELAY, M: Mode> ErrorType
@@ -107,6 +118,22 @@ where
     B: embedded_hal::digital::ErrorType,
 {
     type Error = Parallel8BitsError<D0, D1, D2, D3, D4, D5, D6, D7, E, RS, B>;
+
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        ErrorKind::Pin(match error {
+            Parallel8BitsError::EError(e) => e.kind(),
+            Parallel8BitsError::RSError(e) => e.kind(),
+            Parallel8BitsError::D0Error(e) => e.kind(),
+            Parallel8BitsError::D1Error(e) => e.kind(),
+            Parallel8BitsError::D2Error(e) => e.kind(),
+            Parallel8BitsError::D3Error(e) => e.kind(),
+            Parallel8BitsError::D4Error(e) => e.kind(),
+            Parallel8BitsError::D5Error(e) => e.kind(),
+            Parallel8BitsError::D6Error(e) => e.kind(),
+            Parallel8BitsError::D7Error(e) => e.kind(),
+            Parallel8BitsError::BacklightError(e) => e.kind(),
+        })
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -128,38 +155,58 @@ where
     B: OutputPin,
 {
     fn set_outputs(&mut self, data: u8) -> Result<(), <Self as ErrorType>::Error> {
-        // Set the data bits
-        self.d7
-            .set_state((data & (1 << 7) != 0).into())
-            .map_err(Parallel8BitsError::D7Error)?;
-        self.d6
-            .set_state((data & (1 << 6) != 0).into())
-            .map_err(Parallel8BitsError::D6Error)?;
-        self.d5
-            .set_state((data & (1 << 5) != 0).into())
-            .map_err(Parallel8BitsError::D5Error)?;
-        self.d4
-            .set_state((data & (1 << 4) != 0).into())
-            .map_err(Parallel8BitsError::D4Error)?;
-        self.d3
-            .set_state((data & (1 << 3) != 0).into())
-            .map_err(Parallel8BitsError::D3Error)?;
-        self.d2
-            .set_state((data & (1 << 2) != 0).into())
-            .map_err(Parallel8BitsError::D2Error)?;
-        self.d1
-            .set_state((data & (1 << 1) != 0).into())
-            .map_err(Parallel8BitsError::D1Error)?;
-        self.d0
-            .set_state((data & (1 << 0) != 0).into())
-            .map_err(Parallel8BitsError::D0Error)
+        // Only drive the data pins whose level changes
+        let changed = match self.data.take() {
+            Some(previous) => previous ^ data,
+            None => 0b1111_1111,
+        };
+        if changed & (1 << 7) != 0 {
+            self.d7
+                .set_state((data & (1 << 7) != 0).into())
+                .map_err(Parallel8BitsError::D7Error)?;
+        }
+        if changed & (1 << 6) != 0 {
+            self.d6
+                .set_state((data & (1 << 6) != 0).into())
+                .map_err(Parallel8BitsError::D6Error)?;
+        }
+        if changed & (1 << 5) != 0 {
+            self.d5
+                .set_state((data & (1 << 5) != 0).into())
+                .map_err(Parallel8BitsError::D5Error)?;
+        }
+        if changed & (1 << 4) != 0 {
+            self.d4
+                .set_state((data & (1 << 4) != 0).into())
+                .map_err(Parallel8BitsError::D4Error)?;
+        }
+        if changed & (1 << 3) != 0 {
+            self.d3
+                .set_state((data & (1 << 3) != 0).into())
+                .map_err(Parallel8BitsError::D3Error)?;
+        }
+        if changed & (1 << 2) != 0 {
+            self.d2
+                .set_state((data & (1 << 2) != 0).into())
+                .map_err(Parallel8BitsError::D2Error)?;
+        }
+        if changed & (1 << 1) != 0 {
+            self.d1
+                .set_state((data & (1 << 1) != 0).into())
+                .map_err(Parallel8BitsError::D1Error)?;
+        }
+        if changed & (1 << 0) != 0 {
+            self.d0
+                .set_state((data & (1 << 0) != 0).into())
+                .map_err(Parallel8BitsError::D0Error)?;
+        }
+        self.data = Some(data);
+        Ok(())
     }
 
     fn _backlight(&mut self, enable: bool) -> Result<(), <Self as ErrorType>::Error> {
-        if self.backlight.is_some() {
-            self.backlight
-                .as_mut()
-                .unwrap()
+        if let Some(backlight) = self.backlight.as_mut() {
+            backlight
                 .set_state(enable.into())
                 .map_err(Parallel8BitsError::BacklightError)?;
         }
@@ -211,6 +258,9 @@ where
             rs,
             delay,
             backlight: None,
+            timing: Timing::default(),
+            command: None,
+            data: None,
             _mode: PhantomData,
         }
     }
@@ -243,24 +293,35 @@ where
     E: OutputPin,
     RS: OutputPin,
 {
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
     fn write_byte(&mut self, data: u8) -> Result<(), Self::Error> {
         // Set the output pin levels
         self.set_outputs(data)?;
         // Open the latch
         self.e.set_high().map_err(Parallel8BitsError::EError)?;
         // Wait for the controller to fetch the data
-        self.delay.delay_ns(500);
+        self.delay.delay_ns(self.timing.enable_pulse_ns);
         // Close the latch
         self.e.set_low().map_err(Parallel8BitsError::EError)?;
-        self.delay.delay_ns(500);
+        self.delay.delay_ns(self.timing.enable_hold_ns);
         Ok(())
     }
 
     #[inline]
     fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        if self.command == Some(command) {
+            return Ok(());
+        }
+        self.command = None;
         self.rs
             .set_state((!command).into())
-            .map_err(Parallel8BitsError::RSError)
+            .map_err(Parallel8BitsError::RSError)?;
+        self.command = Some(command);
+        Ok(())
     }
 }
 
@@ -305,49 +366,69 @@ where
     B: AsyncOutputPin,
 {
     async fn set_outputs(&mut self, data: u8) -> Result<(), <Self as ErrorType>::Error> {
-        // Set the data bits
-        self.d7
-            .set_state((data & (1 << 7) != 0).into())
-            .await
-            .map_err(Parallel8BitsError::D7Error)?;
-        self.d6
-            .set_state((data & (1 << 6) != 0).into())
-            .await
-            .map_err(Parallel8BitsError::D6Error)?;
-        self.d5
-            .set_state((data & (1 << 5) != 0).into())
-            .await
-            .map_err(Parallel8BitsError::D5Error)?;
-        self.d4
-            .set_state((data & (1 << 4) != 0).into())
-            .await
-            .map_err(Parallel8BitsError::D4Error)?;
-        self.d3
-            .set_state((data & (1 << 3) != 0).into())
-            .await
-            .map_err(Parallel8BitsError::D3Error)?;
-        self.d2
-            .set_state((data & (1 << 2) != 0).into())
-            .await
-            .map_err(Parallel8BitsError::D2Error)?;
-        self.d1
-            .set_state((data & (1 << 1) != 0).into())
-            .await
-            .map_err(Parallel8BitsError::D1Error)?;
-        self.d0
-            .set_state((data & (1 << 0) != 0).into())
-            .await
-            .map_err(Parallel8BitsError::D0Error)
+        // Only drive the data pins whose level changes
+        let changed = match self.data.take() {
+            Some(previous) => previous ^ data,
+            None => 0b1111_1111,
+        };
+        if changed & (1 << 7) != 0 {
+            self.d7
+                .set_state((data & (1 << 7) != 0).into())
+                .await
+                .map_err(Parallel8BitsError::D7Error)?;
+        }
+        if changed & (1 << 6) != 0 {
+            self.d6
+                .set_state((data & (1 << 6) != 0).into())
+                .await
+                .map_err(Parallel8BitsError::D6Error)?;
+        }
+        if changed & (1 << 5) != 0 {
+            self.d5
+                .set_state((data & (1 << 5) != 0).into())
+                .await
+                .map_err(Parallel8BitsError::D5Error)?;
+        }
+        if changed & (1 << 4) != 0 {
+            self.d4
+                .set_state((data & (1 << 4) != 0).into())
+                .await
+                .map_err(Parallel8BitsError::D4Error)?;
+        }
+        if changed & (1 << 3) != 0 {
+            self.d3
+                .set_state((data & (1 << 3) != 0).into())
+                .await
+                .map_err(Parallel8BitsError::D3Error)?;
+        }
+        if changed & (1 << 2) != 0 {
+            self.d2
+                .set_state((data & (1 << 2) != 0).into())
+                .await
+                .map_err(Parallel8BitsError::D2Error)?;
+        }
+        if changed & (1 << 1) != 0 {
+            self.d1
+                .set_state((data & (1 << 1) != 0).into())
+                .await
+                .map_err(Parallel8BitsError::D1Error)?;
+        }
+        if changed & (1 << 0) != 0 {
+            self.d0
+                .set_state((data & (1 << 0) != 0).into())
+                .await
+                .map_err(Parallel8BitsError::D0Error)?;
+        }
+        self.data = Some(data);
+        Ok(())
     }
 
     async fn _backlight(
         &mut self,
         enable: bool,
     ) -> Result<(), Parallel8BitsError<D0, D1, D2, D3, D4, D5, D6, D7, E, RS, B>> {
-        if self.backlight.is_some() {
-            self.backlight
-                .as_mut()
-                .unwrap()
+        if let Some(backlight) = self.backlight.as_mut() {
+            backlight
                 .set_state(enable.into())
                 .await
                 .map_err(Parallel8BitsError::BacklightError)?;
@@ -399,6 +480,9 @@ where
             rs,
             delay,
             backlight: None,
+            timing: Timing::default(),
+            command: None,
+            data: None,
             _mode: PhantomData,
         }
     }
@@ -431,6 +515,11 @@ where
     E: AsyncOutputPin,
     RS: AsyncOutputPin,
 {
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
     async fn write_byte(&mut self, data: u8) -> Result<(), Self::Error> {
         // Set the output pin levels
         self.set_outputs(data).await?;
@@ -440,20 +529,26 @@ where
             .await
             .map_err(Parallel8BitsError::EError)?;
         // Wait for the controller to fetch the data
-        self.delay.delay_ns(500).await;
+        self.delay.delay_ns(self.timing.enable_pulse_ns).await;
         // Close the latch
         self.e.set_low().await.map_err(Parallel8BitsError::EError)?;
         // Wait until we can send the next data
-        self.delay.delay_ns(500).await;
+        self.delay.delay_ns(self.timing.enable_hold_ns).await;
         Ok(())
     }
 
     #[inline]
     async fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        if self.command == Some(command) {
+            return Ok(());
+        }
+        self.command = None;
         self.rs
             .set_state((!command).into())
             .await
-            .map_err(Parallel8BitsError::RSError)
+            .map_err(Parallel8BitsError::RSError)?;
+        self.command = Some(command);
+        Ok(())
     }
 }
 
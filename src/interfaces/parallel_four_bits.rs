@@ -1,11 +1,12 @@
 use crate::async_output_pin::AsyncOutputPin;
 use crate::interfaces::{
-    Async4BitBus, AsyncInterface, Blocking4BitBus, BlockingInterface, ErrorType, FourBitBus,
+    Async4BitBus, AsyncInterface, Blocking4BitBus, BlockingInterface, ErrorKind, ErrorType,
+    FourBitBus,
 };
-use crate::{Async, Blocking, Mode};
+use crate::{Async, Blocking, Mode, Timing};
 use core::fmt::{Debug, Formatter};
 use core::marker::PhantomData;
-use embedded_hal::digital::OutputPin;
+use embedded_hal::digital::{Error, OutputPin};
 
 pub enum Parallel4BitsError<
     D7: embedded_hal::digital::ErrorType,
@@ -58,6 +59,10 @@ pub struct Parallel4Bits<D7, D6, D5, D4, E, RS, B, DELAY, M: Mode> {
     rs: RS,
     delay: DELAY,
     backlight: Option<B>,
+    timing: Timing,
+    // Last levels of RS and the data pins, if known
+    command: Option<bool>,
+    data: Option<u8>,
     _mode: PhantomData<M>,
 }
 
@@ -66,6 +71,12 @@ impl<D7, D6, D5, D4, E, RS, B, DELAY, M: Mode> Parallel4Bits<D7, D6, D5, D4, E,
         self.backlight = Some(backlight);
         self
     }
+
+    #[inline]
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
 }
 
 impl<D7, D6, D5, D4, E, RS, B, DELAY, M: Mode> ErrorType
@@ -80,6 +91,18 @@ where
     B: embedded_hal::digital::ErrorType,
 {
     type Error = Parallel4BitsError<D7, D6, D5, D4, E, RS, B>;
+
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        ErrorKind::Pin(match error {
+            Parallel4BitsError::EError(e) => e.kind(),
+            Parallel4BitsError::RSError(e) => e.kind(),
+            Parallel4BitsError::D7Error(e) => e.kind(),
+            Parallel4BitsError::D6Error(e) => e.kind(),
+            Parallel4BitsError::D5Error(e) => e.kind(),
+            Parallel4BitsError::D4Error(e) => e.kind(),
+            Parallel4BitsError::BacklightError(e) => e.kind(),
+        })
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -96,27 +119,39 @@ where
     B: OutputPin,
 {
     fn set_outputs(&mut self, data: u8) -> Result<(), <Self as ErrorType>::Error> {
-        // Set the data bits
-        self.d7
-            .set_state((data & (1 << 3) != 0).into())
-            .map_err(Parallel4BitsError::D7Error)?;
-        self.d6
-            .set_state((data & (1 << 2) != 0).into())
-            .map_err(Parallel4BitsError::D6Error)?;
-        self.d5
-            .set_state((data & (1 << 1) != 0).into())
-            .map_err(Parallel4BitsError::D5Error)?;
-        self.d4
-            .set_state((data & (1 << 0) != 0).into())
-            .map_err(Parallel4BitsError::D4Error)
+        // Only drive the data pins whose level changes
+        let changed = match self.data.take() {
+            Some(previous) => previous ^ data,
+            None => 0b0000_1111,
+        };
+        if changed & (1 << 3) != 0 {
+            self.d7
+                .set_state((data & (1 << 3) != 0).into())
+                .map_err(Parallel4BitsError::D7Error)?;
+        }
+        if changed & (1 << 2) != 0 {
+            self.d6
+                .set_state((data & (1 << 2) != 0).into())
+                .map_err(Parallel4BitsError::D6Error)?;
+        }
+        if changed & (1 << 1) != 0 {
+            self.d5
+                .set_state((data & (1 << 1) != 0).into())
+                .map_err(Parallel4BitsError::D5Error)?;
+        }
+        if changed & (1 << 0) != 0 {
+            self.d4
+                .set_state((data & (1 << 0) != 0).into())
+                .map_err(Parallel4BitsError::D4Error)?;
+        }
+        self.data = Some(data & 0b0000_1111);
+        Ok(())
     }
 
     #[inline]
     fn _backlight(&mut self, enable: bool) -> Result<(), <Self as ErrorType>::Error> {
-        if self.backlight.is_some() {
-            self.backlight
-                .as_mut()
-                .unwrap()
+        if let Some(backlight) = self.backlight.as_mut() {
+            backlight
                 .set_state(enable.into())
                 .map_err(Parallel4BitsError::BacklightError)?;
         }
@@ -145,6 +180,9 @@ where
             rs,
             delay,
             backlight: None,
+            timing: Timing::default(),
+            command: None,
+            data: None,
             _mode: PhantomData,
         }
     }
@@ -185,18 +223,24 @@ where
         self.set_outputs(data)?;
         // Open the latch
         self.e.set_high().map_err(Parallel4BitsError::EError)?;
-        self.delay.delay_ns(500);
+        self.delay.delay_ns(self.timing.enable_pulse_ns);
         // Close the latch
         self.e.set_low().map_err(Parallel4BitsError::EError)?;
-        self.delay.delay_ns(500);
+        self.delay.delay_ns(self.timing.enable_hold_ns);
         Ok(())
     }
 
     #[inline]
     fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        if self.command == Some(command) {
+            return Ok(());
+        }
+        self.command = None;
         self.rs
             .set_state((!command).into())
-            .map_err(Parallel4BitsError::RSError)
+            .map_err(Parallel4BitsError::RSError)?;
+        self.command = Some(command);
+        Ok(())
     }
 }
 
@@ -235,23 +279,37 @@ where
         &mut self,
         data: u8,
     ) -> Result<(), Parallel4BitsError<D7, D6, D5, D4, E, RS, B>> {
-        // Set the data bits
-        self.d7
-            .set_state((data & (1 << 3) != 0).into())
-            .await
-            .map_err(Parallel4BitsError::D7Error)?;
-        self.d6
-            .set_state((data & (1 << 2) != 0).into())
-            .await
-            .map_err(Parallel4BitsError::D6Error)?;
-        self.d5
-            .set_state((data & (1 << 1) != 0).into())
-            .await
-            .map_err(Parallel4BitsError::D5Error)?;
-        self.d4
-            .set_state((data & (1 << 0) != 0).into())
-            .await
-            .map_err(Parallel4BitsError::D4Error)
+        // Only drive the data pins whose level changes
+        let changed = match self.data.take() {
+            Some(previous) => previous ^ data,
+            None => 0b0000_1111,
+        };
+        if changed & (1 << 3) != 0 {
+            self.d7
+                .set_state((data & (1 << 3) != 0).into())
+                .await
+                .map_err(Parallel4BitsError::D7Error)?;
+        }
+        if changed & (1 << 2) != 0 {
+            self.d6
+                .set_state((data & (1 << 2) != 0).into())
+                .await
+                .map_err(Parallel4BitsError::D6Error)?;
+        }
+        if changed & (1 << 1) != 0 {
+            self.d5
+                .set_state((data & (1 << 1) != 0).into())
+                .await
+                .map_err(Parallel4BitsError::D5Error)?;
+        }
+        if changed & (1 << 0) != 0 {
+            self.d4
+                .set_state((data & (1 << 0) != 0).into())
+                .await
+                .map_err(Parallel4BitsError::D4Error)?;
+        }
+        self.data = Some(data & 0b0000_1111);
+        Ok(())
     }
 
     #[inline]
@@ -259,10 +317,8 @@ where
         &mut self,
         enable: bool,
     ) -> Result<(), Parallel4BitsError<D7, D6, D5, D4, E, RS, B>> {
-        if self.backlight.is_some() {
-            self.backlight
-                .as_mut()
-                .unwrap()
+        if let Some(backlight) = self.backlight.as_mut() {
+            backlight
                 .set_state(enable.into())
                 .await
                 .map_err(Parallel4BitsError::BacklightError)?;
@@ -293,6 +349,9 @@ where
             rs,
             delay,
             backlight: None,
+            timing: Timing::default(),
+            command: None,
+            data: None,
             _mode: PhantomData,
         }
     }
@@ -340,19 +399,25 @@ where
             .await
             .map_err(Parallel4BitsError::EError)?;
         // Wait for the controller to fetch the data
-        self.delay.delay_ns(500).await;
+        self.delay.delay_ns(self.timing.enable_pulse_ns).await;
         // Close the latch
         self.e.set_low().await.map_err(Parallel4BitsError::EError)?;
-        self.delay.delay_ns(500).await;
+        self.delay.delay_ns(self.timing.enable_hold_ns).await;
         Ok(())
     }
 
     #[inline]
     async fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        if self.command == Some(command) {
+            return Ok(());
+        }
+        self.command = None;
         self.rs
             .set_state((!command).into())
             .await
-            .map_err(Parallel4BitsError::RSError)
+            .map_err(Parallel4BitsError::RSError)?;
+        self.command = Some(command);
+        Ok(())
     }
 }
 
@@ -0,0 +1,51 @@
+use embedded_hal::i2c::ErrorKind;
+
+/// Retries of failed I2C transactions.
+///
+/// Every transaction is a single write of the expander state, so repeating it is harmless even
+/// if the failed attempt reached the expander. The presets cover the common cases, adjust the
+/// fields for others.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Attempts per transaction including the first one
+    pub max_attempts: u8,
+    /// Wait before each retry in µs
+    pub backoff_us: u32,
+    /// Errors worth another attempt
+    pub retryable: fn(ErrorKind) -> bool,
+}
+
+impl RetryPolicy {
+    /// Every error is returned right away
+    pub const NONE: Self = Self {
+        max_attempts: 1,
+        backoff_us: 0,
+        retryable: Self::transient,
+    };
+
+    /// A few attempts for the errors caused by noise on long cables
+    pub const TRANSIENT: Self = Self {
+        max_attempts: 3,
+        backoff_us: 100,
+        retryable: Self::transient,
+    };
+
+    pub fn transient(kind: ErrorKind) -> bool {
+        matches!(
+            kind,
+            ErrorKind::NoAcknowledge(_) | ErrorKind::ArbitrationLoss | ErrorKind::Bus
+        )
+    }
+
+    // Whether the transaction is tried again after failing the given attempt
+    #[inline]
+    pub(crate) fn retry(&self, attempt: u8, kind: ErrorKind) -> bool {
+        attempt < self.max_attempts && (self.retryable)(kind)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
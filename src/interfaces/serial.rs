@@ -0,0 +1,230 @@
+use crate::interfaces::{
+    Async8BitBus, AsyncInterface, Blocking8BitBus, BlockingInterface, EightBitBus, ErrorKind,
+    ErrorType,
+};
+use crate::{Async, Blocking, Mode, Timing};
+use core::marker::PhantomData;
+
+// Synchronization bits, RW and RS in transmission order. RW is always 0 as the bus is write only.
+const START_BYTE: u8 = 0b1111_1000;
+const RS: u8 = 0b0000_0010;
+
+/// Order in which the controller expects the data bits on the serial line.
+#[derive(Default, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum BitOrder {
+    #[default]
+    LsbFirst,
+    MsbFirst,
+}
+
+/// Framing of the synchronous serial interface.
+///
+/// Every transfer starts with a start byte carrying the RW and RS bits. The data byte follows
+/// either as a whole, or split into two bytes with the nibble in the upper half, as used by the
+/// ST7070, SPLC780D and KS0073 (`LsbFirst`, low nibble first) or the ST7920 (`MsbFirst`, high
+/// nibble first).
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct SerialFraming {
+    pub bit_order: BitOrder,
+    pub split_nibbles: bool,
+}
+
+impl Default for SerialFraming {
+    fn default() -> Self {
+        Self {
+            bit_order: BitOrder::LsbFirst,
+            split_nibbles: true,
+        }
+    }
+}
+
+impl SerialFraming {
+    // Encodes a transfer in transmission order, as the SPI peripheral shifts out MSB first
+    fn encode(&self, data: u8, command: bool) -> ([u8; 3], usize) {
+        let start = match command {
+            true => START_BYTE,
+            false => START_BYTE | RS,
+        };
+        match (self.split_nibbles, self.bit_order) {
+            (false, BitOrder::MsbFirst) => ([start, data, 0], 2),
+            (false, BitOrder::LsbFirst) => ([start, data.reverse_bits(), 0], 2),
+            (true, BitOrder::MsbFirst) => ([start, data & 0xF0, data << 4], 3),
+            (true, BitOrder::LsbFirst) => (
+                [
+                    start,
+                    (data & 0x0F).reverse_bits(),
+                    (data >> 4).reverse_bits(),
+                ],
+                3,
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Serial<SPI, DELAY, M: Mode> {
+    spi: SPI,
+    delay: DELAY,
+    framing: SerialFraming,
+    command: bool,
+    timing: Timing,
+    _mode: PhantomData<M>,
+}
+
+impl<SPI, DELAY, M: Mode> Serial<SPI, DELAY, M> {
+    #[inline]
+    pub fn with_framing(mut self, framing: SerialFraming) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    #[inline]
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
+}
+
+impl<SPI, DELAY, M: Mode> ErrorType for Serial<SPI, DELAY, M>
+where
+    SPI: embedded_hal::spi::ErrorType,
+{
+    type Error = SPI::Error;
+
+    #[inline]
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        ErrorKind::Spi(embedded_hal::spi::Error::kind(error))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// BLOCKING INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<SPI, DELAY> Serial<SPI, DELAY, Blocking>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    pub fn new(spi: SPI, delay: DELAY) -> Self {
+        Self {
+            spi,
+            delay,
+            framing: SerialFraming::default(),
+            command: true,
+            timing: Timing::default(),
+            _mode: PhantomData,
+        }
+    }
+}
+
+impl<SPI, DELAY> embedded_hal::delay::DelayNs for Serial<SPI, DELAY, Blocking>
+where
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns);
+    }
+}
+
+impl<SPI, DELAY> Blocking8BitBus for Serial<SPI, DELAY, Blocking>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
+    fn write_byte(&mut self, data: u8) -> Result<(), Self::Error> {
+        let (frame, len) = self.framing.encode(data, self.command);
+        self.spi.write(&frame[..len])
+    }
+
+    #[inline]
+    fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        // RS is part of the start byte
+        self.command = command;
+        Ok(())
+    }
+}
+
+impl<SPI, DELAY> BlockingInterface<EightBitBus> for Serial<SPI, DELAY, Blocking>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    fn backlight(&mut self, _enable: bool) -> Result<(), Self::Error> {
+        // The serial interface has no backlight control
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// ASYNC INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<SPI, DELAY> Serial<SPI, DELAY, Async>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    pub fn new_async(spi: SPI, delay: DELAY) -> Self {
+        Self {
+            spi,
+            delay,
+            framing: SerialFraming::default(),
+            command: true,
+            timing: Timing::default(),
+            _mode: PhantomData,
+        }
+    }
+}
+
+impl<SPI, DELAY> embedded_hal_async::delay::DelayNs for Serial<SPI, DELAY, Async>
+where
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    async fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns).await;
+    }
+}
+
+impl<SPI, DELAY> Async8BitBus for Serial<SPI, DELAY, Async>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
+    async fn write_byte(&mut self, data: u8) -> Result<(), Self::Error> {
+        let (frame, len) = self.framing.encode(data, self.command);
+        self.spi.write(&frame[..len]).await
+    }
+
+    #[inline]
+    async fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        // RS is part of the start byte
+        self.command = command;
+        Ok(())
+    }
+}
+
+impl<SPI, DELAY> AsyncInterface<EightBitBus> for Serial<SPI, DELAY, Async>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    async fn backlight(&mut self, _enable: bool) -> Result<(), Self::Error> {
+        // The serial interface has no backlight control
+        Ok(())
+    }
+}
@@ -0,0 +1,491 @@
+use crate::interfaces::{
+    Async4BitBus, Async8BitBus, AsyncInterface, Blocking4BitBus, Blocking8BitBus,
+    BlockingInterface, EightBitBus, ErrorKind, ErrorType, FourBitBus,
+};
+use crate::{Async, Blocking, Mode, Timing};
+use core::marker::PhantomData;
+
+/// Bit positions of the LCD signals on the shift register outputs.
+///
+/// For a single 74HC595 the positions are `0..8` (`Q0`..`Q7`). When two registers are chained,
+/// positions `0..8` are the outputs of the register connected to the MCU and `8..16` the outputs
+/// of the second one. `data[0]` is the lowest data line of the bus (`D4` on a 4-bit bus, `D0` on
+/// an 8-bit bus).
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct ShiftRegisterLayout<const N: usize> {
+    pub rs: u8,
+    pub e: u8,
+    pub backlight: Option<u8>,
+    pub data: [u8; N],
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum LayoutError {
+    // The position lies beyond the outputs of the shift registers
+    PinOutOfRange(u8),
+}
+
+impl Default for ShiftRegisterLayout<4> {
+    // Same wiring as the common PCF8574 backpacks: RS, RW, E, BL, D4 .. D7
+    fn default() -> Self {
+        Self {
+            rs: 0,
+            e: 2,
+            backlight: Some(3),
+            data: [4, 5, 6, 7],
+        }
+    }
+}
+
+impl Default for ShiftRegisterLayout<8> {
+    // D0 .. D7 on the first register, RS, RW, E, BL on the second one
+    fn default() -> Self {
+        Self {
+            rs: 8,
+            e: 10,
+            backlight: Some(11),
+            data: [0, 1, 2, 3, 4, 5, 6, 7],
+        }
+    }
+}
+
+impl<const N: usize> ShiftRegisterLayout<N> {
+    // Checks that every signal is on one of the given number of outputs
+    pub(crate) fn check(&self, outputs: u8) -> Result<(), LayoutError> {
+        let mut pins = self
+            .data
+            .iter()
+            .chain([&self.rs, &self.e])
+            .chain(self.backlight.as_ref());
+        match pins.find(|pin| **pin >= outputs) {
+            Some(pin) => Err(LayoutError::PinOutOfRange(*pin)),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn encode(&self, data: u8, command: bool, enable: bool, backlight: bool) -> u16 {
+        let mut word = 0u16;
+        for (i, bit) in self.data.iter().enumerate() {
+            if data & (1 << i) != 0 {
+                word |= 1 << bit;
+            }
+        }
+        if !command {
+            word |= 1 << self.rs;
+        }
+        if enable {
+            word |= 1 << self.e;
+        }
+        if let (true, Some(bit)) = (backlight, self.backlight) {
+            word |= 1 << bit;
+        }
+        word
+    }
+}
+
+#[derive(Debug)]
+pub struct ShiftRegister4Bits<SPI, DELAY, M: Mode> {
+    spi: SPI,
+    delay: DELAY,
+    layout: ShiftRegisterLayout<4>,
+    command: bool,
+    // Whether the register outputs carry the current RS level
+    latched: bool,
+    backlight: bool,
+    timing: Timing,
+    _mode: PhantomData<M>,
+}
+
+#[derive(Debug)]
+pub struct ShiftRegister8Bits<SPI, DELAY, M: Mode> {
+    spi: SPI,
+    delay: DELAY,
+    layout: ShiftRegisterLayout<8>,
+    command: bool,
+    // Whether the register outputs carry the current RS level
+    latched: bool,
+    backlight: bool,
+    timing: Timing,
+    _mode: PhantomData<M>,
+}
+
+impl<SPI, DELAY, M: Mode> ShiftRegister4Bits<SPI, DELAY, M> {
+    // Fails if a signal is not on one of the eight outputs of the register
+    #[inline]
+    pub fn with_layout(mut self, layout: ShiftRegisterLayout<4>) -> Result<Self, LayoutError> {
+        layout.check(8)?;
+        self.layout = layout;
+        Ok(self)
+    }
+
+    #[inline]
+    fn outputs(&self, data: u8, enable: bool) -> [u8; 1] {
+        [self
+            .layout
+            .encode(data, self.command, enable, self.backlight) as u8]
+    }
+
+    #[inline]
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
+}
+
+impl<SPI, DELAY, M: Mode> ShiftRegister8Bits<SPI, DELAY, M> {
+    // Fails if a signal is not on one of the sixteen outputs of the two registers
+    #[inline]
+    pub fn with_layout(mut self, layout: ShiftRegisterLayout<8>) -> Result<Self, LayoutError> {
+        layout.check(16)?;
+        self.layout = layout;
+        Ok(self)
+    }
+
+    #[inline]
+    fn outputs(&self, data: u8, enable: bool) -> [u8; 2] {
+        // The first byte is shifted through to the second register
+        self.layout
+            .encode(data, self.command, enable, self.backlight)
+            .to_be_bytes()
+    }
+
+    #[inline]
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
+}
+
+impl<SPI, DELAY, M: Mode> ErrorType for ShiftRegister4Bits<SPI, DELAY, M>
+where
+    SPI: embedded_hal::spi::ErrorType,
+{
+    type Error = SPI::Error;
+
+    #[inline]
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        ErrorKind::Spi(embedded_hal::spi::Error::kind(error))
+    }
+}
+
+impl<SPI, DELAY, M: Mode> ErrorType for ShiftRegister8Bits<SPI, DELAY, M>
+where
+    SPI: embedded_hal::spi::ErrorType,
+{
+    type Error = SPI::Error;
+
+    #[inline]
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        ErrorKind::Spi(embedded_hal::spi::Error::kind(error))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// BLOCKING INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<SPI, DELAY> ShiftRegister4Bits<SPI, DELAY, Blocking>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    pub fn new(spi: SPI, delay: DELAY) -> Self {
+        Self {
+            spi,
+            delay,
+            layout: ShiftRegisterLayout::default(),
+            command: true,
+            latched: false,
+            backlight: false,
+            timing: Timing::default(),
+            _mode: PhantomData,
+        }
+    }
+}
+
+impl<SPI, DELAY> ShiftRegister8Bits<SPI, DELAY, Blocking>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    pub fn new(spi: SPI, delay: DELAY) -> Self {
+        Self {
+            spi,
+            delay,
+            layout: ShiftRegisterLayout::default(),
+            command: true,
+            latched: false,
+            backlight: false,
+            timing: Timing::default(),
+            _mode: PhantomData,
+        }
+    }
+}
+
+impl<SPI, DELAY> embedded_hal::delay::DelayNs for ShiftRegister4Bits<SPI, DELAY, Blocking>
+where
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns);
+    }
+}
+
+impl<SPI, DELAY> embedded_hal::delay::DelayNs for ShiftRegister8Bits<SPI, DELAY, Blocking>
+where
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns);
+    }
+}
+
+impl<SPI, DELAY> Blocking4BitBus for ShiftRegister4Bits<SPI, DELAY, Blocking>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
+    fn write_nibble(&mut self, nibble: u8) -> Result<(), Self::Error> {
+        // Shift out the data and open the latch
+        self.spi.write(&self.outputs(nibble, true))?;
+        // Wait for the controller to fetch the data
+        self.delay.delay_ns(self.timing.enable_pulse_ns);
+        // Close the latch again
+        self.spi.write(&self.outputs(nibble, false))?;
+        self.delay.delay_ns(self.timing.enable_hold_ns);
+        Ok(())
+    }
+
+    #[inline]
+    fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        // Every transfer carries RS, so it only has to be shifted out on a change
+        if self.latched && self.command == command {
+            return Ok(());
+        }
+        self.command = command;
+        self.latched = false;
+        self.spi.write(&self.outputs(0, false))?;
+        self.latched = true;
+        Ok(())
+    }
+}
+
+impl<SPI, DELAY> BlockingInterface<FourBitBus> for ShiftRegister4Bits<SPI, DELAY, Blocking>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    fn backlight(&mut self, enable: bool) -> Result<(), Self::Error> {
+        self.backlight = enable;
+        self.spi.write(&self.outputs(0, false))
+    }
+}
+
+impl<SPI, DELAY> Blocking8BitBus for ShiftRegister8Bits<SPI, DELAY, Blocking>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
+    fn write_byte(&mut self, data: u8) -> Result<(), Self::Error> {
+        // Shift out the data and open the latch
+        self.spi.write(&self.outputs(data, true))?;
+        // Wait for the controller to fetch the data
+        self.delay.delay_ns(self.timing.enable_pulse_ns);
+        // Close the latch again
+        self.spi.write(&self.outputs(data, false))?;
+        self.delay.delay_ns(self.timing.enable_hold_ns);
+        Ok(())
+    }
+
+    #[inline]
+    fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        // Every transfer carries RS, so it only has to be shifted out on a change
+        if self.latched && self.command == command {
+            return Ok(());
+        }
+        self.command = command;
+        self.latched = false;
+        self.spi.write(&self.outputs(0, false))?;
+        self.latched = true;
+        Ok(())
+    }
+}
+
+impl<SPI, DELAY> BlockingInterface<EightBitBus> for ShiftRegister8Bits<SPI, DELAY, Blocking>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    fn backlight(&mut self, enable: bool) -> Result<(), Self::Error> {
+        self.backlight = enable;
+        self.spi.write(&self.outputs(0, false))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// ASYNC INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<SPI, DELAY> ShiftRegister4Bits<SPI, DELAY, Async>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    pub fn new_async(spi: SPI, delay: DELAY) -> Self {
+        Self {
+            spi,
+            delay,
+            layout: ShiftRegisterLayout::default(),
+            command: true,
+            latched: false,
+            backlight: false,
+            timing: Timing::default(),
+            _mode: PhantomData,
+        }
+    }
+}
+
+impl<SPI, DELAY> ShiftRegister8Bits<SPI, DELAY, Async>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    pub fn new_async(spi: SPI, delay: DELAY) -> Self {
+        Self {
+            spi,
+            delay,
+            layout: ShiftRegisterLayout::default(),
+            command: true,
+            latched: false,
+            backlight: false,
+            timing: Timing::default(),
+            _mode: PhantomData,
+        }
+    }
+}
+
+impl<SPI, DELAY> embedded_hal_async::delay::DelayNs for ShiftRegister4Bits<SPI, DELAY, Async>
+where
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    async fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns).await;
+    }
+}
+
+impl<SPI, DELAY> embedded_hal_async::delay::DelayNs for ShiftRegister8Bits<SPI, DELAY, Async>
+where
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    async fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns).await;
+    }
+}
+
+impl<SPI, DELAY> Async4BitBus for ShiftRegister4Bits<SPI, DELAY, Async>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
+    async fn write_nibble(&mut self, nibble: u8) -> Result<(), Self::Error> {
+        // Shift out the data and open the latch
+        self.spi.write(&self.outputs(nibble, true)).await?;
+        // Wait for the controller to fetch the data
+        self.delay.delay_ns(self.timing.enable_pulse_ns).await;
+        // Close the latch again
+        self.spi.write(&self.outputs(nibble, false)).await?;
+        self.delay.delay_ns(self.timing.enable_hold_ns).await;
+        Ok(())
+    }
+
+    #[inline]
+    async fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        // Every transfer carries RS, so it only has to be shifted out on a change
+        if self.latched && self.command == command {
+            return Ok(());
+        }
+        self.command = command;
+        self.latched = false;
+        self.spi.write(&self.outputs(0, false)).await?;
+        self.latched = true;
+        Ok(())
+    }
+}
+
+impl<SPI, DELAY> AsyncInterface<FourBitBus> for ShiftRegister4Bits<SPI, DELAY, Async>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    async fn backlight(&mut self, enable: bool) -> Result<(), Self::Error> {
+        self.backlight = enable;
+        self.spi.write(&self.outputs(0, false)).await
+    }
+}
+
+impl<SPI, DELAY> Async8BitBus for ShiftRegister8Bits<SPI, DELAY, Async>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
+    async fn write_byte(&mut self, data: u8) -> Result<(), Self::Error> {
+        // Shift out the data and open the latch
+        self.spi.write(&self.outputs(data, true)).await?;
+        // Wait for the controller to fetch the data
+        self.delay.delay_ns(self.timing.enable_pulse_ns).await;
+        // Close the latch again
+        self.spi.write(&self.outputs(data, false)).await?;
+        self.delay.delay_ns(self.timing.enable_hold_ns).await;
+        Ok(())
+    }
+
+    #[inline]
+    async fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        // Every transfer carries RS, so it only has to be shifted out on a change
+        if self.latched && self.command == command {
+            return Ok(());
+        }
+        self.command = command;
+        self.latched = false;
+        self.spi.write(&self.outputs(0, false)).await?;
+        self.latched = true;
+        Ok(())
+    }
+}
+
+impl<SPI, DELAY> AsyncInterface<EightBitBus> for ShiftRegister8Bits<SPI, DELAY, Async>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    async fn backlight(&mut self, enable: bool) -> Result<(), Self::Error> {
+        self.backlight = enable;
+        self.spi.write(&self.outputs(0, false)).await
+    }
+}
@@ -0,0 +1,324 @@
+use crate::interfaces::{
+    AsyncBus, AsyncInterface, AsyncRgbBacklight, BusWidth, ErrorKind, ErrorType,
+};
+use crate::{Command, Timing};
+use core::future::{poll_fn, Future};
+use core::pin::pin;
+use core::task::Poll;
+use embedded_hal_async::delay::DelayNs;
+
+// Writes of the initialization by instruction, the last one switching to the 4-bit mode
+const INIT_WRITES: u32 = 4;
+
+#[derive(Debug)]
+pub enum TimeoutError<E> {
+    // The bus call did not complete in time and has been abandoned
+    Timeout,
+    InterfaceError(E),
+}
+
+/// Limits the time each bus call of an async interface may take.
+///
+/// Every transfer is raced against a deadline on the given timer. A call which misses it is
+/// dropped and fails with `TimeoutError::Timeout`. The `Display` then resynchronizes the bus
+/// before its next operation, so the operation can simply be retried. The waits for the
+/// execution of the instructions are added to the deadline and still use the wrapped interface.
+#[derive(Debug)]
+pub struct Timeout<T, TIMER> {
+    interface: T,
+    timer: TIMER,
+    timeout_us: u32,
+}
+
+impl<T, TIMER: DelayNs> Timeout<T, TIMER> {
+    #[inline]
+    pub fn new(interface: T, timer: TIMER, timeout_us: u32) -> Self {
+        Self {
+            interface,
+            timer,
+            timeout_us,
+        }
+    }
+
+    #[inline]
+    pub fn release(self) -> (T, TIMER) {
+        (self.interface, self.timer)
+    }
+}
+
+// Polls the future until it completes or the timer expires
+async fn race<F, R, E, TIMER>(timer: &mut TIMER, us: u32, future: F) -> Result<R, TimeoutError<E>>
+where
+    F: Future<Output = Result<R, E>>,
+    TIMER: DelayNs,
+{
+    let mut future = pin!(future);
+    let mut deadline = pin!(timer.delay_us(us));
+    poll_fn(|context| {
+        if let Poll::Ready(result) = future.as_mut().poll(context) {
+            return Poll::Ready(result.map_err(TimeoutError::InterfaceError));
+        }
+        match deadline.as_mut().poll(context) {
+            Poll::Ready(()) => {
+                #[cfg(feature = "log")]
+                log::warn!("LCD bus call timed out after {} µs", us);
+                Poll::Ready(Err(TimeoutError::Timeout))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    })
+    .await
+}
+
+impl<T: ErrorType, TIMER> ErrorType for Timeout<T, TIMER> {
+    type Error = TimeoutError<T::Error>;
+
+    #[inline]
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        match error {
+            TimeoutError::Timeout => ErrorKind::Timeout,
+            TimeoutError::InterfaceError(e) => T::error_kind(e),
+        }
+    }
+}
+
+impl<T: DelayNs, TIMER> DelayNs for Timeout<T, TIMER> {
+    #[inline]
+    async fn delay_ns(&mut self, ns: u32) {
+        self.interface.delay_ns(ns).await;
+    }
+}
+
+impl<W, T, TIMER> AsyncBus<W> for Timeout<T, TIMER>
+where
+    W: BusWidth,
+    T: AsyncBus<W>,
+    TIMER: DelayNs,
+{
+    #[inline]
+    fn timing(&self) -> &Timing {
+        self.interface.timing()
+    }
+
+    async fn initialize(&mut self) -> Result<(), Self::Error> {
+        let timing = self.interface.timing();
+        let waits = timing.init_long_us + timing.init_short_us + 2 * timing.execution_us;
+        race(
+            &mut self.timer,
+            self.timeout_us
+                .saturating_mul(INIT_WRITES)
+                .saturating_add(waits),
+            self.interface.initialize(),
+        )
+        .await
+    }
+
+    #[inline]
+    async fn write(&mut self, data: u8, command: bool) -> Result<(), Self::Error> {
+        race(
+            &mut self.timer,
+            self.timeout_us,
+            self.interface.write(data, command),
+        )
+        .await
+    }
+
+    // Grants every byte the time of a single write, plus the waits for its execution in between
+    async fn write_bytes(
+        &mut self,
+        data: &[u8],
+        command: bool,
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        let count = u32::try_from(data.len()).unwrap_or(u32::MAX);
+        let waits = count.saturating_sub(1).saturating_mul(timing.execution_us);
+        race(
+            &mut self.timer,
+            self.timeout_us.saturating_mul(count).saturating_add(waits),
+            self.interface.write_bytes(data, command, timing),
+        )
+        .await
+    }
+
+    // Grants every command the time of a single write, plus the waits for their execution
+    async fn write_many(
+        &mut self,
+        commands: &[Command],
+        timing: &Timing,
+    ) -> Result<(), Self::Error> {
+        let count = u32::try_from(commands.len()).unwrap_or(u32::MAX);
+        let waits = commands
+            .iter()
+            .fold(0u32, |sum, c| sum.saturating_add(c.delay_us(timing)));
+        race(
+            &mut self.timer,
+            self.timeout_us.saturating_mul(count).saturating_add(waits),
+            self.interface.write_many(commands, timing),
+        )
+        .await
+    }
+}
+
+impl<W, T, TIMER> AsyncInterface<W> for Timeout<T, TIMER>
+where
+    W: BusWidth,
+    T: AsyncInterface<W>,
+    TIMER: DelayNs,
+{
+    #[inline]
+    async fn backlight(&mut self, enable: bool) -> Result<(), Self::Error> {
+        race(
+            &mut self.timer,
+            self.timeout_us,
+            self.interface.backlight(enable),
+        )
+        .await
+    }
+}
+
+impl<T, TIMER> AsyncRgbBacklight for Timeout<T, TIMER>
+where
+    T: AsyncRgbBacklight,
+    TIMER: DelayNs,
+{
+    #[inline]
+    async fn set_color(&mut self, red: u8, green: u8, blue: u8) -> Result<(), Self::Error> {
+        race(
+            &mut self.timer,
+            self.timeout_us,
+            self.interface.set_color(red, green, blue),
+        )
+        .await
+    }
+
+    #[inline]
+    async fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error> {
+        race(
+            &mut self.timer,
+            self.timeout_us,
+            self.interface.set_brightness(brightness),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::{BlockOn, EightBitBus, SpinBlockOn};
+    use crate::mock::Recorder;
+    use crate::Display;
+    use core::cell::Cell;
+    use core::convert::Infallible;
+    use core::future::{pending, ready};
+
+    // Bus whose transfers never complete while stalled
+    struct Stalling<'a> {
+        bus: &'a mut Recorder,
+        stall: &'a Cell<bool>,
+    }
+
+    impl Stalling<'_> {
+        async fn check(&self) {
+            if self.stall.get() {
+                pending::<()>().await;
+            }
+        }
+    }
+
+    impl ErrorType for Stalling<'_> {
+        type Error = Infallible;
+    }
+
+    impl DelayNs for Stalling<'_> {
+        async fn delay_ns(&mut self, _ns: u32) {}
+    }
+
+    impl AsyncBus<EightBitBus> for Stalling<'_> {
+        async fn initialize(&mut self) -> Result<(), Self::Error> {
+            self.check().await;
+            AsyncBus::initialize(self.bus).await
+        }
+
+        async fn write(&mut self, data: u8, command: bool) -> Result<(), Self::Error> {
+            self.check().await;
+            AsyncBus::write(self.bus, data, command).await
+        }
+    }
+
+    impl AsyncInterface<EightBitBus> for Stalling<'_> {
+        async fn backlight(&mut self, _enable: bool) -> Result<(), Self::Error> {
+            self.check().await;
+            Ok(())
+        }
+    }
+
+    // Timer which expires right away, recording the deadlines as they are set
+    #[derive(Default)]
+    struct Timer {
+        deadlines: Vec<u32>,
+    }
+
+    impl DelayNs for Timer {
+        async fn delay_ns(&mut self, _ns: u32) {}
+
+        fn delay_us(&mut self, us: u32) -> impl Future<Output = ()> {
+            self.deadlines.push(us);
+            ready(())
+        }
+    }
+
+    #[test]
+    fn timed_out_call_resyncs() {
+        let mut bus = Recorder::default();
+        let stall = Cell::new(true);
+        let interface = Stalling {
+            bus: &mut bus,
+            stall: &stall,
+        };
+        let mut display = Display::new_async(Timeout::new(interface, Timer::default(), 100));
+        SpinBlockOn.block_on(async {
+            let result = display.write_byte(b'a').await;
+            assert!(matches!(result, Err(TimeoutError::Timeout)));
+            stall.set(false);
+            display.write_byte(b'b').await.unwrap();
+        });
+        drop(display);
+        assert_eq!(bus.initializations, 1);
+        // Function set, display control, entry mode and cursor restored before the retry
+        assert_eq!(bus.instructions(), [0x30, 0x08, 0x04, 0x80]);
+        assert_eq!(bus.data(), b"b");
+    }
+
+    #[test]
+    fn write_bytes_deadline_grows_with_the_data() {
+        let mut timeout = Timeout::new(Recorder::default(), Timer::default(), 100);
+        SpinBlockOn
+            .block_on(AsyncBus::write_bytes(
+                &mut timeout,
+                &[b'x'; 20],
+                false,
+                &Timing::HD44780,
+            ))
+            .unwrap();
+        let (_, timer) = timeout.release();
+        let execution_us = Timing::HD44780.execution_us;
+        assert_eq!(timer.deadlines, [20 * 100 + 19 * execution_us]);
+    }
+
+    #[test]
+    fn write_many_deadline_grows_with_the_commands() {
+        let mut timeout = Timeout::new(Recorder::default(), Timer::default(), 100);
+        let commands = [Command::Clear, Command::Data(b'x'), Command::Data(b'y')];
+        SpinBlockOn
+            .block_on(AsyncBus::write_many(
+                &mut timeout,
+                &commands,
+                &Timing::HD44780,
+            ))
+            .unwrap();
+        let (_, timer) = timeout.release();
+        let waits = Timing::HD44780.clear_us + 2 * Timing::HD44780.execution_us;
+        assert_eq!(timer.deadlines, [3 * 100 + waits]);
+    }
+}
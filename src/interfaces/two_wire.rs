@@ -0,0 +1,289 @@
+use crate::async_output_pin::AsyncOutputPin;
+use crate::interfaces::{
+    Async4BitBus, AsyncInterface, Blocking4BitBus, BlockingInterface, ErrorKind, ErrorType,
+    FourBitBus, LayoutError, ShiftRegisterLayout,
+};
+use crate::{Async, Blocking, Mode, Timing};
+use core::fmt::{Debug, Formatter};
+use core::marker::PhantomData;
+use embedded_hal::digital::{Error, OutputPin};
+
+pub enum TwoWireError<DATA: embedded_hal::digital::ErrorType, CLK: embedded_hal::digital::ErrorType>
+{
+    DataError(DATA::Error),
+    ClockError(CLK::Error),
+}
+
+impl<DATA, CLK> Debug for TwoWireError<DATA, CLK>
+where
+    DATA: embedded_hal::digital::ErrorType,
+    CLK: embedded_hal::digital::ErrorType,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TwoWireError::DataError(e) => write!(f, "{:?}", e),
+            TwoWireError::ClockError(e) => write!(f, "{:?}", e),
+        }
+    }
+}
+
+// Wiring of the "LCD 2-wire" circuit: BL, RS, D4 .. D7 and the enable gate on the last output.
+// E is driven by the AND of this output and the data line.
+const LAYOUT: ShiftRegisterLayout<4> = ShiftRegisterLayout {
+    rs: 2,
+    e: 7,
+    backlight: Some(1),
+    data: [3, 4, 5, 6],
+};
+
+#[derive(Debug)]
+pub struct TwoWire<DATA, CLK, DELAY, M: Mode> {
+    data: DATA,
+    clock: CLK,
+    delay: DELAY,
+    layout: ShiftRegisterLayout<4>,
+    command: bool,
+    backlight: bool,
+    timing: Timing,
+    _mode: PhantomData<M>,
+}
+
+impl<DATA, CLK, DELAY, M: Mode> TwoWire<DATA, CLK, DELAY, M> {
+    // Fails if a signal is not on one of the eight outputs of the register
+    #[inline]
+    pub fn with_layout(mut self, layout: ShiftRegisterLayout<4>) -> Result<Self, LayoutError> {
+        layout.check(8)?;
+        self.layout = layout;
+        Ok(self)
+    }
+
+    #[inline]
+    fn outputs(&self, data: u8, enable: bool) -> u8 {
+        self.layout
+            .encode(data, self.command, enable, self.backlight) as u8
+    }
+
+    #[inline]
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
+}
+
+impl<DATA, CLK, DELAY, M: Mode> ErrorType for TwoWire<DATA, CLK, DELAY, M>
+where
+    DATA: embedded_hal::digital::ErrorType,
+    CLK: embedded_hal::digital::ErrorType,
+{
+    type Error = TwoWireError<DATA, CLK>;
+
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        ErrorKind::Pin(match error {
+            TwoWireError::DataError(e) => e.kind(),
+            TwoWireError::ClockError(e) => e.kind(),
+        })
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// BLOCKING INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<DATA, CLK, DELAY> TwoWire<DATA, CLK, DELAY, Blocking>
+where
+    DATA: OutputPin,
+    CLK: OutputPin,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    pub fn new(data: DATA, clock: CLK, delay: DELAY) -> Self {
+        Self {
+            data,
+            clock,
+            delay,
+            layout: LAYOUT,
+            command: true,
+            backlight: false,
+            timing: Timing::default(),
+            _mode: PhantomData,
+        }
+    }
+
+    fn clock_bit(&mut self, bit: bool) -> Result<(), <Self as ErrorType>::Error> {
+        self.data
+            .set_state(bit.into())
+            .map_err(TwoWireError::DataError)?;
+        self.clock.set_high().map_err(TwoWireError::ClockError)?;
+        self.clock.set_low().map_err(TwoWireError::ClockError)
+    }
+
+    fn load(&mut self, value: u8) -> Result<(), <Self as ErrorType>::Error> {
+        // Clear the register first, so that the enable gate stays closed while shifting
+        for _ in 0..8 {
+            self.clock_bit(false)?;
+        }
+        // Shift in the new value in MSB first order
+        for i in (0..8).rev() {
+            self.clock_bit(value & (1 << i) != 0)?;
+        }
+        Ok(())
+    }
+}
+
+impl<DATA, CLK, DELAY> embedded_hal::delay::DelayNs for TwoWire<DATA, CLK, DELAY, Blocking>
+where
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns);
+    }
+}
+
+impl<DATA, CLK, DELAY> Blocking4BitBus for TwoWire<DATA, CLK, DELAY, Blocking>
+where
+    DATA: OutputPin,
+    CLK: OutputPin,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
+    fn write_nibble(&mut self, nibble: u8) -> Result<(), Self::Error> {
+        // Load the data with the enable gate bit set
+        self.load(self.outputs(nibble, true))?;
+        // Open the latch through the AND gate
+        self.data.set_high().map_err(TwoWireError::DataError)?;
+        // Wait for the controller to fetch the data
+        self.delay.delay_ns(self.timing.enable_pulse_ns);
+        // Close the latch again
+        self.data.set_low().map_err(TwoWireError::DataError)?;
+        self.delay.delay_ns(self.timing.enable_hold_ns);
+        Ok(())
+    }
+
+    #[inline]
+    fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        // RS is loaded together with every nibble
+        self.command = command;
+        Ok(())
+    }
+}
+
+impl<DATA, CLK, DELAY> BlockingInterface<FourBitBus> for TwoWire<DATA, CLK, DELAY, Blocking>
+where
+    DATA: OutputPin,
+    CLK: OutputPin,
+    DELAY: embedded_hal::delay::DelayNs,
+{
+    fn backlight(&mut self, enable: bool) -> Result<(), Self::Error> {
+        self.backlight = enable;
+        self.load(self.outputs(0, false))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// ASYNC INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<DATA, CLK, DELAY> TwoWire<DATA, CLK, DELAY, Async>
+where
+    DATA: AsyncOutputPin,
+    CLK: AsyncOutputPin,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    pub fn new_async(data: DATA, clock: CLK, delay: DELAY) -> Self {
+        Self {
+            data,
+            clock,
+            delay,
+            layout: LAYOUT,
+            command: true,
+            backlight: false,
+            timing: Timing::default(),
+            _mode: PhantomData,
+        }
+    }
+
+    async fn clock_bit(&mut self, bit: bool) -> Result<(), <Self as ErrorType>::Error> {
+        self.data
+            .set_state(bit.into())
+            .await
+            .map_err(TwoWireError::DataError)?;
+        self.clock
+            .set_high()
+            .await
+            .map_err(TwoWireError::ClockError)?;
+        self.clock.set_low().await.map_err(TwoWireError::ClockError)
+    }
+
+    async fn load(&mut self, value: u8) -> Result<(), <Self as ErrorType>::Error> {
+        // Clear the register first, so that the enable gate stays closed while shifting
+        for _ in 0..8 {
+            self.clock_bit(false).await?;
+        }
+        // Shift in the new value in MSB first order
+        for i in (0..8).rev() {
+            self.clock_bit(value & (1 << i) != 0).await?;
+        }
+        Ok(())
+    }
+}
+
+impl<DATA, CLK, DELAY> embedded_hal_async::delay::DelayNs for TwoWire<DATA, CLK, DELAY, Async>
+where
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    async fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns).await;
+    }
+}
+
+impl<DATA, CLK, DELAY> Async4BitBus for TwoWire<DATA, CLK, DELAY, Async>
+where
+    DATA: AsyncOutputPin,
+    CLK: AsyncOutputPin,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    #[inline]
+    fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
+    async fn write_nibble(&mut self, nibble: u8) -> Result<(), Self::Error> {
+        // Load the data with the enable gate bit set
+        self.load(self.outputs(nibble, true)).await?;
+        // Open the latch through the AND gate
+        self.data
+            .set_high()
+            .await
+            .map_err(TwoWireError::DataError)?;
+        // Wait for the controller to fetch the data
+        self.delay.delay_ns(self.timing.enable_pulse_ns).await;
+        // Close the latch again
+        self.data.set_low().await.map_err(TwoWireError::DataError)?;
+        self.delay.delay_ns(self.timing.enable_hold_ns).await;
+        Ok(())
+    }
+
+    #[inline]
+    async fn set_command_mode(&mut self, command: bool) -> Result<(), Self::Error> {
+        // RS is loaded together with every nibble
+        self.command = command;
+        Ok(())
+    }
+}
+
+impl<DATA, CLK, DELAY> AsyncInterface<FourBitBus> for TwoWire<DATA, CLK, DELAY, Async>
+where
+    DATA: AsyncOutputPin,
+    CLK: AsyncOutputPin,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    async fn backlight(&mut self, enable: bool) -> Result<(), Self::Error> {
+        self.backlight = enable;
+        self.load(self.outputs(0, false)).await
+    }
+}
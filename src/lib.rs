@@ -1,14 +1,41 @@
 #![cfg_attr(not(test), no_std)]
 #![allow(async_fn_in_trait)]
 
+pub mod controllers;
 pub mod interfaces;
 
 mod async_output_pin;
 pub use async_output_pin::AsyncOutputPin;
+mod batch;
+mod clock;
+mod command;
 mod display;
+mod dyn_lcd;
+#[cfg(feature = "graphics")]
+mod graphics;
+#[cfg(feature = "io")]
+mod matrix_orbital;
+#[cfg(test)]
+mod mock;
+#[cfg(feature = "nb")]
+mod nb_display;
+mod supervisor;
+mod timing;
 
+pub use batch::{Batch, BatchError, BATCH_CAPACITY};
+pub use clock::{Clock, NoClock};
+pub use command::Command;
 use core::fmt::Formatter;
 pub use display::Display;
+pub use dyn_lcd::{DynLcd, DynLcdError};
+#[cfg(feature = "graphics")]
+pub use graphics::GraphicsDisplay;
+#[cfg(feature = "io")]
+pub use matrix_orbital::{MatrixOrbitalError, MatrixOrbitalServer};
+#[cfg(feature = "nb")]
+pub use nb_display::{NbDisplay, QUEUE_CAPACITY};
+pub use supervisor::Supervisor;
+pub use timing::Timing;
 
 mod private {
     pub trait Sealed {}
@@ -34,12 +61,13 @@ pub enum Font {
     _5x10 = 4,
 }
 
-#[repr(u8)]
 #[derive(Default, Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Lines {
     #[default]
-    _1 = 0,
-    _2 = 8,
+    _1,
+    _2,
+    _3,
+    _4,
 }
 
 impl core::fmt::Display for Lines {
@@ -47,6 +75,8 @@ impl core::fmt::Display for Lines {
         match self {
             Lines::_1 => write!(f, "one"),
             Lines::_2 => write!(f, "two"),
+            Lines::_3 => write!(f, "three"),
+            Lines::_4 => write!(f, "four"),
         }
     }
 }
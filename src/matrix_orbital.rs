@@ -0,0 +1,495 @@
+use crate::controllers::Controller;
+use crate::interfaces::{AsyncInterface, BlockingInterface, BusWidth};
+use crate::{Async, Blocking, Clock, Cursor, Display, Lines, Mode};
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+// Prefix of every command
+pub(crate) const PREFIX: u8 = 0xFE;
+
+// Commands
+pub(crate) const CLEAR: u8 = 0x58;
+pub(crate) const HOME: u8 = 0x48;
+pub(crate) const GOTO: u8 = 0x47;
+pub(crate) const CURSOR_LEFT: u8 = 0x4C;
+pub(crate) const CURSOR_RIGHT: u8 = 0x4D;
+pub(crate) const UNDERLINE_ON: u8 = 0x4A;
+pub(crate) const UNDERLINE_OFF: u8 = 0x4B;
+pub(crate) const BLINK_ON: u8 = 0x53;
+pub(crate) const BLINK_OFF: u8 = 0x54;
+pub(crate) const BACKLIGHT_ON: u8 = 0x42;
+pub(crate) const BACKLIGHT_OFF: u8 = 0x46;
+pub(crate) const CUSTOM_CHAR: u8 = 0x4E;
+pub(crate) const BRIGHTNESS: u8 = 0x99;
+pub(crate) const RGB: u8 = 0xD0;
+
+// Commands which are accepted but have no effect on the display
+const CONTRAST: u8 = 0x50;
+const SAVE_CONTRAST: u8 = 0x91;
+const SAVE_BRIGHTNESS: u8 = 0x98;
+const GPO_OFF: u8 = 0x56;
+const GPO_ON: u8 = 0x57;
+
+const MAX_PARAMETERS: usize = 9;
+const READ_BUFFER_SIZE: usize = 32;
+
+#[derive(Debug)]
+pub enum MatrixOrbitalError<R, D> {
+    ReadError(R),
+    DisplayError(D),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum State {
+    Text,
+    Command,
+    Parameters(u8, usize),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Event {
+    Character(u8),
+    Command(u8, [u8; MAX_PARAMETERS]),
+}
+
+/// Drives a local display from the Matrix Orbital command stream used by LCDproc's `MtxOrb`
+/// driver, e.g. to build a serial LCD backpack.
+///
+/// Text is written at the cursor, commands are prefixed with `0xFE`. Commands for contrast,
+/// brightness and the general purpose outputs are consumed without effect. The rows are mapped to
+/// the lines of the display, positions are 1-based like in the protocol. Positions beyond the size
+/// of the display, 20x4 unless configured otherwise, are limited to its last column and row.
+#[derive(Debug)]
+pub struct MatrixOrbitalServer<R, M: Mode> {
+    reader: R,
+    state: State,
+    parameters: [u8; MAX_PARAMETERS],
+    column: u8,
+    row: u8,
+    columns: u8,
+    rows: u8,
+    underline: bool,
+    blink: bool,
+    _mode: PhantomData<M>,
+}
+
+impl<R, M: Mode> MatrixOrbitalServer<R, M> {
+    #[inline]
+    fn create(reader: R) -> Self {
+        Self {
+            reader,
+            state: State::Text,
+            parameters: [0; MAX_PARAMETERS],
+            column: 0,
+            row: 0,
+            columns: 20,
+            rows: 4,
+            underline: false,
+            blink: false,
+            _mode: PhantomData,
+        }
+    }
+
+    // Size of the display in characters, with up to four rows
+    #[inline]
+    pub fn with_size(mut self, columns: u8, rows: u8) -> Self {
+        self.columns = columns.max(1);
+        self.rows = rows.clamp(1, 4);
+        self
+    }
+
+    #[inline]
+    pub fn release(self) -> R {
+        self.reader
+    }
+
+    // Number of parameter bytes following the command
+    fn parameter_count(command: u8) -> usize {
+        match command {
+            CUSTOM_CHAR => 9,
+            RGB => 3,
+            GOTO => 2,
+            BACKLIGHT_ON | CONTRAST | SAVE_CONTRAST | BRIGHTNESS | SAVE_BRIGHTNESS | GPO_OFF
+            | GPO_ON => 1,
+            _ => 0,
+        }
+    }
+
+    fn feed(&mut self, byte: u8) -> Option<Event> {
+        match self.state {
+            State::Text => match byte {
+                PREFIX => {
+                    self.state = State::Command;
+                    None
+                }
+                _ => Some(Event::Character(byte)),
+            },
+            State::Command => self.parameter(byte, 0),
+            State::Parameters(command, received) => {
+                self.parameters[received] = byte;
+                self.parameter(command, received + 1)
+            }
+        }
+    }
+
+    fn parameter(&mut self, command: u8, received: usize) -> Option<Event> {
+        if received < Self::parameter_count(command) {
+            self.state = State::Parameters(command, received);
+            return None;
+        }
+        self.state = State::Text;
+        Some(Event::Command(command, self.parameters))
+    }
+
+    fn cursor(&self) -> Cursor {
+        match (self.underline, self.blink) {
+            (false, false) => Cursor::Disabled,
+            (true, false) => Cursor::Enabled,
+            // The HD44780 has no blinking block without the underline
+            (_, true) => Cursor::Blinking,
+        }
+    }
+
+    fn line(&self) -> Lines {
+        match self.row {
+            0 => Lines::_1,
+            1 => Lines::_2,
+            2 => Lines::_3,
+            _ => Lines::_4,
+        }
+    }
+
+    #[inline]
+    fn goto(&mut self, column: u8, row: u8) {
+        self.column = column.saturating_sub(1).min(self.columns - 1);
+        self.row = row.saturating_sub(1).min(self.rows - 1);
+    }
+
+    // The cursor stops right after the last column
+    #[inline]
+    fn advance(&mut self) {
+        self.column = self.column.saturating_add(1).min(self.columns);
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// BLOCKING INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<R> MatrixOrbitalServer<R, Blocking>
+where
+    R: embedded_io::Read,
+{
+    #[inline]
+    pub fn new(reader: R) -> Self {
+        Self::create(reader)
+    }
+
+    // Reads the available bytes and applies them to the display. Returns the number of bytes
+    // read, which is 0 at the end of the stream.
+    pub fn poll<I, W, C, K>(
+        &mut self,
+        display: &mut Display<I, W, Blocking, C, K>,
+    ) -> Result<usize, MatrixOrbitalError<R::Error, I::Error>>
+    where
+        W: BusWidth,
+        I: BlockingInterface<W>,
+        C: Controller,
+        K: Clock,
+    {
+        let mut buffer = [0; READ_BUFFER_SIZE];
+        let len = self
+            .reader
+            .read(&mut buffer)
+            .map_err(MatrixOrbitalError::ReadError)?;
+        for byte in &buffer[..len] {
+            if let Some(event) = self.feed(*byte) {
+                self.apply(display, event)
+                    .map_err(MatrixOrbitalError::DisplayError)?;
+            }
+        }
+        Ok(len)
+    }
+
+    // Processes the stream until its end
+    pub fn run<I, W, C, K>(
+        &mut self,
+        display: &mut Display<I, W, Blocking, C, K>,
+    ) -> Result<(), MatrixOrbitalError<R::Error, I::Error>>
+    where
+        W: BusWidth,
+        I: BlockingInterface<W>,
+        C: Controller,
+        K: Clock,
+    {
+        while self.poll(display)? > 0 {}
+        Ok(())
+    }
+
+    fn apply<I, W, C, K>(
+        &mut self,
+        display: &mut Display<I, W, Blocking, C, K>,
+        event: Event,
+    ) -> Result<(), I::Error>
+    where
+        W: BusWidth,
+        I: BlockingInterface<W>,
+        C: Controller,
+        K: Clock,
+    {
+        let (command, parameters) = match event {
+            Event::Character(c) => {
+                self.advance();
+                return display.write_byte(c);
+            }
+            Event::Command(command, parameters) => (command, parameters),
+        };
+        #[cfg(feature = "log")]
+        log::debug!("Matrix Orbital command {:#04x}", command);
+        match command {
+            CLEAR => {
+                self.goto(1, 1);
+                display.clear()
+            }
+            HOME => {
+                self.goto(1, 1);
+                display.home()
+            }
+            GOTO => {
+                self.goto(parameters[0], parameters[1]);
+                display.pos(self.line(), self.column)
+            }
+            CURSOR_LEFT => {
+                self.column = self.column.saturating_sub(1);
+                display.pos(self.line(), self.column)
+            }
+            CURSOR_RIGHT => {
+                self.advance();
+                display.pos(self.line(), self.column)
+            }
+            UNDERLINE_ON | UNDERLINE_OFF => {
+                self.underline = command == UNDERLINE_ON;
+                display.set_cursor(self.cursor())
+            }
+            BLINK_ON | BLINK_OFF => {
+                self.blink = command == BLINK_ON;
+                display.set_cursor(self.cursor())
+            }
+            BACKLIGHT_ON => display.enable_backlight(),
+            BACKLIGHT_OFF => display.disable_backlight(),
+            CUSTOM_CHAR => {
+                let mut bitmap = [0; 8];
+                bitmap.copy_from_slice(&parameters[1..]);
+                display.set_custom_char(parameters[0], &bitmap)?;
+                // Leave the CGRAM again
+                display.pos(self.line(), self.column)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// ASYNC INTERFACE
+// -------------------------------------------------------------------------------------------------
+impl<R> MatrixOrbitalServer<R, Async>
+where
+    R: embedded_io_async::Read,
+{
+    #[inline]
+    pub fn new_async(reader: R) -> Self {
+        Self::create(reader)
+    }
+
+    // Reads the available bytes and applies them to the display. Returns the number of bytes
+    // read, which is 0 at the end of the stream.
+    pub async fn poll<I, W, C, K>(
+        &mut self,
+        display: &mut Display<I, W, Async, C, K>,
+    ) -> Result<usize, MatrixOrbitalError<R::Error, I::Error>>
+    where
+        W: BusWidth,
+        I: AsyncInterface<W>,
+        C: Controller,
+        K: Clock,
+    {
+        let mut buffer = [0; READ_BUFFER_SIZE];
+        let len = self
+            .reader
+            .read(&mut buffer)
+            .await
+            .map_err(MatrixOrbitalError::ReadError)?;
+        for byte in &buffer[..len] {
+            if let Some(event) = self.feed(*byte) {
+                self.apply(display, event)
+                    .await
+                    .map_err(MatrixOrbitalError::DisplayError)?;
+            }
+        }
+        Ok(len)
+    }
+
+    // Processes the stream until its end
+    pub async fn run<I, W, C, K>(
+        &mut self,
+        display: &mut Display<I, W, Async, C, K>,
+    ) -> Result<(), MatrixOrbitalError<R::Error, I::Error>>
+    where
+        W: BusWidth,
+        I: AsyncInterface<W>,
+        C: Controller,
+        K: Clock,
+    {
+        while self.poll(display).await? > 0 {}
+        Ok(())
+    }
+
+    async fn apply<I, W, C, K>(
+        &mut self,
+        display: &mut Display<I, W, Async, C, K>,
+        event: Event,
+    ) -> Result<(), I::Error>
+    where
+        W: BusWidth,
+        I: AsyncInterface<W>,
+        C: Controller,
+        K: Clock,
+    {
+        let (command, parameters) = match event {
+            Event::Character(c) => {
+                self.advance();
+                return display.write_byte(c).await;
+            }
+            Event::Command(command, parameters) => (command, parameters),
+        };
+        #[cfg(feature = "log")]
+        log::debug!("Matrix Orbital command {:#04x}", command);
+        match command {
+            CLEAR => {
+                self.goto(1, 1);
+                display.clear().await
+            }
+            HOME => {
+                self.goto(1, 1);
+                display.home().await
+            }
+            GOTO => {
+                self.goto(parameters[0], parameters[1]);
+                display.pos(self.line(), self.column).await
+            }
+            CURSOR_LEFT => {
+                self.column = self.column.saturating_sub(1);
+                display.pos(self.line(), self.column).await
+            }
+            CURSOR_RIGHT => {
+                self.advance();
+                display.pos(self.line(), self.column).await
+            }
+            UNDERLINE_ON | UNDERLINE_OFF => {
+                self.underline = command == UNDERLINE_ON;
+                display.set_cursor(self.cursor()).await
+            }
+            BLINK_ON | BLINK_OFF => {
+                self.blink = command == BLINK_ON;
+                display.set_cursor(self.cursor()).await
+            }
+            BACKLIGHT_ON => display.enable_backlight().await,
+            BACKLIGHT_OFF => display.disable_backlight().await,
+            CUSTOM_CHAR => {
+                let mut bitmap = [0; 8];
+                bitmap.copy_from_slice(&parameters[1..]);
+                display.set_custom_char(parameters[0], &bitmap).await?;
+                // Leave the CGRAM again
+                display.pos(self.line(), self.column).await
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Recorder;
+    use core::convert::Infallible;
+
+    // Hands the stream out in reads of at most `size` bytes
+    struct Chunked<'a> {
+        data: &'a [u8],
+        size: usize,
+    }
+
+    impl embedded_io::ErrorType for Chunked<'_> {
+        type Error = Infallible;
+    }
+
+    impl embedded_io::Read for Chunked<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            let len = self.size.min(buf.len()).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    fn serve(input: &[u8], size: usize) -> Recorder {
+        let mut display = Display::new(Recorder::default());
+        let reader = Chunked { data: input, size };
+        MatrixOrbitalServer::new(reader).run(&mut display).unwrap();
+        core::mem::take(display.interface_mut())
+    }
+
+    #[test]
+    fn text() {
+        let bus = serve(b"Hello", READ_BUFFER_SIZE);
+        assert_eq!(bus.data(), b"Hello");
+        assert!(bus.instructions().is_empty());
+    }
+
+    #[test]
+    fn goto_is_one_based() {
+        let bus = serve(&[PREFIX, GOTO, 3, 2, b'x'], READ_BUFFER_SIZE);
+        assert_eq!(bus.writes, [(0x80 | 0x42, true), (b'x', false)]);
+    }
+
+    #[test]
+    fn command_split_across_reads() {
+        let bus = serve(&[b'a', PREFIX, GOTO, 1, 2, b'b'], 1);
+        assert_eq!(
+            bus.writes,
+            [(b'a', false), (0x80 | 0x40, true), (b'b', false)]
+        );
+    }
+
+    #[test]
+    fn custom_char() {
+        let bitmap = [1, 2, 3, 4, 5, 6, 7, 8];
+        let mut input = vec![PREFIX, CUSTOM_CHAR, 2];
+        input.extend_from_slice(&bitmap);
+        input.push(b'z');
+        let bus = serve(&input, 4);
+        // CGRAM address of the character, its rows, back to the cursor position
+        assert_eq!(bus.instructions(), [0x40 | 2 << 3, 0x80]);
+        assert_eq!(bus.data(), [&bitmap[..], b"z"].concat());
+    }
+
+    #[test]
+    fn ignored_commands() {
+        let input = [
+            PREFIX, CONTRAST, 0x80, PREFIX, GPO_ON, 1, PREFIX, GPO_OFF, 1, b'A',
+        ];
+        let bus = serve(&input, READ_BUFFER_SIZE);
+        assert_eq!(bus.writes, [(b'A', false)]);
+    }
+
+    #[test]
+    fn out_of_range_positions() {
+        // Last column of the fourth row of a 20x4 display
+        let bus = serve(&[PREFIX, GOTO, 0xFF, 0xFF], READ_BUFFER_SIZE);
+        assert_eq!(bus.instructions(), [0x80 | (0x54 + 19)]);
+
+        let mut input = vec![b'x'; 300];
+        input.extend_from_slice(&[PREFIX, CURSOR_RIGHT, PREFIX, CURSOR_RIGHT]);
+        let bus = serve(&input, READ_BUFFER_SIZE);
+        assert_eq!(bus.instructions(), [0x80 | 20, 0x80 | 20]);
+    }
+}
@@ -0,0 +1,124 @@
+// Test doubles of the bus interfaces shared by the unit tests
+use crate::interfaces::{
+    AsyncBus, AsyncInterface, BlockingBus, BlockingInterface, BlockingProbe, EightBitBus, ErrorType,
+};
+use core::convert::Infallible;
+use embedded_hal::i2c::{ErrorKind, Operation, SevenBitAddress};
+use std::collections::VecDeque;
+
+// Bus which records the bytes written together with the level of RS
+#[derive(Debug, Default)]
+pub(crate) struct Recorder {
+    pub(crate) writes: Vec<(u8, bool)>,
+    pub(crate) initializations: usize,
+    // Whether probing finds the module missing
+    pub(crate) unplugged: bool,
+}
+
+impl Recorder {
+    pub(crate) fn instructions(&self) -> Vec<u8> {
+        self.bytes(true)
+    }
+
+    pub(crate) fn data(&self) -> Vec<u8> {
+        self.bytes(false)
+    }
+
+    fn bytes(&self, command: bool) -> Vec<u8> {
+        self.writes
+            .iter()
+            .filter(|(_, c)| *c == command)
+            .map(|(b, _)| *b)
+            .collect()
+    }
+}
+
+impl ErrorType for Recorder {
+    type Error = Infallible;
+}
+
+impl embedded_hal::delay::DelayNs for Recorder {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
+
+impl BlockingBus<EightBitBus> for Recorder {
+    fn initialize(&mut self) -> Result<(), Self::Error> {
+        self.initializations += 1;
+        Ok(())
+    }
+
+    fn write(&mut self, data: u8, command: bool) -> Result<(), Self::Error> {
+        self.writes.push((data, command));
+        Ok(())
+    }
+}
+
+impl BlockingInterface<EightBitBus> for Recorder {
+    fn backlight(&mut self, _enable: bool) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl BlockingProbe for Recorder {
+    fn probe(&mut self) -> bool {
+        !self.unplugged
+    }
+}
+
+impl embedded_hal_async::delay::DelayNs for Recorder {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
+
+impl AsyncBus<EightBitBus> for Recorder {
+    async fn initialize(&mut self) -> Result<(), Self::Error> {
+        BlockingBus::initialize(self)
+    }
+
+    async fn write(&mut self, data: u8, command: bool) -> Result<(), Self::Error> {
+        BlockingBus::write(self, data, command)
+    }
+}
+
+impl AsyncInterface<EightBitBus> for Recorder {
+    async fn backlight(&mut self, _enable: bool) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+// Delay which returns right away
+#[derive(Debug, Default)]
+pub(crate) struct NoDelay;
+
+impl embedded_hal::delay::DelayNs for NoDelay {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
+
+// I2C bus which records the written transactions. The next transactions fail with the queued
+// errors, failed transactions are not recorded.
+#[derive(Debug, Default)]
+pub(crate) struct I2cBus {
+    pub(crate) transactions: Vec<Vec<u8>>,
+    pub(crate) failures: VecDeque<ErrorKind>,
+}
+
+impl embedded_hal::i2c::ErrorType for I2cBus {
+    type Error = ErrorKind;
+}
+
+impl embedded_hal::i2c::I2c<SevenBitAddress> for I2cBus {
+    fn transaction(
+        &mut self,
+        _address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if let Some(error) = self.failures.pop_front() {
+            return Err(error);
+        }
+        for operation in operations {
+            if let Operation::Write(data) = operation {
+                self.transactions.push(data.to_vec());
+            }
+        }
+        Ok(())
+    }
+}
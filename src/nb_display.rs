@@ -0,0 +1,208 @@
+use crate::controllers::{Controller, Hd44780};
+use crate::display::character_as_byte;
+use crate::interfaces::{BlockingInterface, BusWidth};
+use crate::{Blocking, Clock, Command, Display, Lines, NoClock, Shift, ShiftDirection};
+use core::convert::Infallible;
+
+pub const QUEUE_CAPACITY: usize = 32;
+
+/// Non-blocking front-end of a blocking `Display` for firmware without an executor.
+///
+/// The operations are queued and sent one at a time by `poll`, once the controller has executed
+/// the previous command. Queuing returns `WouldBlock` if the queue lacks space for the whole
+/// operation, so operations longer than `QUEUE_CAPACITY` have to be split. Without a clock, see
+/// `Display::with_clock`, the time until the controller is ready can't be measured, so `poll`
+/// falls back to waiting for it with the delay of the interface.
+#[derive(Debug)]
+pub struct NbDisplay<I, W: BusWidth, C = Hd44780, K = NoClock> {
+    display: Display<I, W, Blocking, C, K>,
+    queue: [Command; QUEUE_CAPACITY],
+    head: usize,
+    len: usize,
+}
+
+impl<I, W, C, K> NbDisplay<I, W, C, K>
+where
+    W: BusWidth,
+    I: BlockingInterface<W>,
+    C: Controller,
+    K: Clock,
+{
+    #[inline]
+    pub fn new(display: Display<I, W, Blocking, C, K>) -> Self {
+        Self {
+            display,
+            queue: [Command::Delay(0); QUEUE_CAPACITY],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    // Returns the display, dropping the commands not sent yet
+    #[inline]
+    pub fn release(self) -> Display<I, W, Blocking, C, K> {
+        self.display
+    }
+
+    // Whether all queued commands have been sent
+    #[inline]
+    pub fn is_idle(&self) -> bool {
+        self.len == 0
+    }
+
+    // Sends the next queued command if the controller is ready. Returns `Ok` once the queue is
+    // empty and the controller has executed the last command, so `nb::block!` flushes the queue.
+    pub fn poll(&mut self) -> nb::Result<(), I::Error> {
+        if !K::MEASURES_TIME {
+            self.display.wait_ready();
+        }
+        if !self.display.is_ready() {
+            return Err(nb::Error::WouldBlock);
+        }
+        let Some(command) = self.pop() else {
+            return Ok(());
+        };
+        self.display.send(command).map_err(nb::Error::Other)?;
+        Err(nb::Error::WouldBlock)
+    }
+
+    pub fn send(&mut self, command: Command) -> nb::Result<(), Infallible> {
+        self.send_all([command])
+    }
+
+    pub fn send_all<S>(&mut self, commands: S) -> nb::Result<(), Infallible>
+    where
+        S: IntoIterator<Item = Command>,
+        S::IntoIter: ExactSizeIterator,
+    {
+        let commands = commands.into_iter();
+        self.reserve(commands.len())?;
+        commands.for_each(|command| self.push(command));
+        Ok(())
+    }
+
+    #[inline]
+    pub fn clear(&mut self) -> nb::Result<(), Infallible> {
+        self.send(Command::Clear)
+    }
+
+    #[inline]
+    pub fn home(&mut self) -> nb::Result<(), Infallible> {
+        self.send(Command::Home)
+    }
+
+    #[inline]
+    pub fn shift(
+        &mut self,
+        shift: Shift,
+        shift_direction: ShiftDirection,
+    ) -> nb::Result<(), Infallible> {
+        self.send(Command::Shift(shift, shift_direction))
+    }
+
+    #[inline]
+    pub fn pos(&mut self, line: Lines, position: u8) -> nb::Result<(), Infallible> {
+        let address = self.display.address(line, position);
+        self.send(Command::SetDdramAddress(address))
+    }
+
+    pub fn set_custom_char(
+        &mut self,
+        location: u8,
+        bitmap: &[u8; 8],
+    ) -> nb::Result<(), Infallible> {
+        self.reserve(1 + bitmap.len())?;
+        self.push(Command::SetCgramAddress((location & 0b0000_0111) << 3));
+        bitmap.iter().for_each(|b| self.push(Command::Data(*b)));
+        Ok(())
+    }
+
+    #[inline]
+    pub fn write_byte(&mut self, data: u8) -> nb::Result<(), Infallible> {
+        self.send(Command::Data(data))
+    }
+
+    pub fn write_bytes(&mut self, data: &[u8]) -> nb::Result<(), Infallible> {
+        self.send_all(data.iter().map(|b| Command::Data(*b)))
+    }
+
+    pub fn write_string(&mut self, s: &str) -> nb::Result<(), Infallible> {
+        self.reserve(s.chars().count())?;
+        s.chars()
+            .for_each(|c| self.push(Command::Data(character_as_byte(c))));
+        Ok(())
+    }
+
+    #[inline]
+    fn reserve(&self, count: usize) -> nb::Result<(), Infallible> {
+        match QUEUE_CAPACITY - self.len >= count {
+            true => Ok(()),
+            false => Err(nb::Error::WouldBlock),
+        }
+    }
+
+    // The space has to be reserved beforehand
+    #[inline]
+    fn push(&mut self, command: Command) {
+        self.queue[(self.head + self.len) % QUEUE_CAPACITY] = command;
+        self.len += 1;
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<Command> {
+        if self.len == 0 {
+            return None;
+        }
+        let command = self.queue[self.head];
+        self.head = (self.head + 1) % QUEUE_CAPACITY;
+        self.len -= 1;
+        Some(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Recorder;
+    use crate::Timing;
+    use core::cell::Cell;
+
+    // Clock advanced by the test
+    struct Ticks<'a>(&'a Cell<u32>);
+
+    impl Clock for Ticks<'_> {
+        fn now_us(&mut self) -> u32 {
+            self.0.get()
+        }
+    }
+
+    #[test]
+    fn flushes_without_clock() {
+        let mut nb = NbDisplay::new(Display::new(Recorder::default()));
+        nb.clear().unwrap();
+        nb.write_string("ab").unwrap();
+        nb::block!(nb.poll()).unwrap();
+        let mut display = nb.release();
+        assert_eq!(
+            display.interface_mut().writes,
+            [(0x01, true), (b'a', false), (b'b', false)]
+        );
+    }
+
+    #[test]
+    fn waits_for_the_clock() {
+        let now = Cell::new(0);
+        let display = Display::new(Recorder::default()).with_clock(Ticks(&now));
+        let mut nb = NbDisplay::new(display);
+        nb.clear().unwrap();
+        nb.write_byte(b'a').unwrap();
+        assert_eq!(nb.poll(), Err(nb::Error::WouldBlock));
+        // The clear is still executing
+        assert_eq!(nb.poll(), Err(nb::Error::WouldBlock));
+        now.set(Timing::HD44780.clear_us);
+        assert_eq!(nb.poll(), Err(nb::Error::WouldBlock));
+        assert!(nb.is_idle());
+        now.set(Timing::HD44780.clear_us + Timing::HD44780.execution_us);
+        assert_eq!(nb.poll(), Ok(()));
+    }
+}
@@ -0,0 +1,399 @@
+use crate::controllers::{Controller, Hd44780};
+use crate::display::{character_as_byte, AddressCounter, INCREMENT};
+use crate::interfaces::{BlockingInterface, BlockingProbe, BusWidth};
+use crate::{Blocking, Clock, Command, Display, Lines, NoClock, Shift, ShiftDirection};
+
+// Size of the DDRAM address space
+const DDRAM_SIZE: usize = 128;
+// Characters per line, after which a display shift wraps around
+const LINE_LENGTH: u8 = 40;
+// Entry mode bit which shifts the display along with every write to the DDRAM
+const AUTOSHIFT: u8 = 0b0000_0001;
+
+/// Keeps a hot-pluggable module in sync with the contents written through it.
+///
+/// All writes are recorded in a shadow of the DDRAM and the custom characters. `poll` probes the
+/// module periodically. Once it answers again after going missing, it is initialized anew and
+/// the custom characters, the screen contents, the display shift and the cursor are restored.
+/// While the module is missing, writes only update the shadow. Writes which bypass the
+/// supervisor, e.g. through `display_mut`, are not restored.
+#[derive(Debug)]
+pub struct Supervisor<I, W: BusWidth, C = Hd44780, K = NoClock> {
+    display: Display<I, W, Blocking, C, K>,
+    present: bool,
+    ddram: [u8; DDRAM_SIZE],
+    // One bit per DDRAM address written since the last clear
+    written: u128,
+    glyphs: [[u8; 8]; 8],
+    // One bit per custom character written
+    uploaded: u8,
+    counter: AddressCounter,
+    // Display shift to the right, modulo the line length
+    scroll: u8,
+    restores: u32,
+}
+
+impl<I, W, C, K> Supervisor<I, W, C, K>
+where
+    W: BusWidth,
+    I: BlockingInterface<W> + BlockingProbe,
+    C: Controller,
+    K: Clock,
+{
+    // Takes an initialized display, whose screen is considered blank
+    #[inline]
+    pub fn new(display: Display<I, W, Blocking, C, K>) -> Self {
+        Self {
+            display,
+            present: true,
+            ddram: [b' '; DDRAM_SIZE],
+            written: 0,
+            glyphs: [[0; 8]; 8],
+            uploaded: 0,
+            counter: AddressCounter::default(),
+            scroll: 0,
+            restores: 0,
+        }
+    }
+
+    #[inline]
+    pub fn release(self) -> Display<I, W, Blocking, C, K> {
+        self.display
+    }
+
+    #[inline]
+    pub fn display(&self) -> &Display<I, W, Blocking, C, K> {
+        &self.display
+    }
+
+    #[inline]
+    pub fn display_mut(&mut self) -> &mut Display<I, W, Blocking, C, K> {
+        &mut self.display
+    }
+
+    #[inline]
+    pub fn is_present(&self) -> bool {
+        self.present
+    }
+
+    // Number of times the module has been restored after coming back
+    #[inline]
+    pub fn restores(&self) -> u32 {
+        self.restores
+    }
+
+    // Probes the module and restores it once it answers again. Returns whether it is present.
+    pub fn poll(&mut self) -> Result<bool, I::Error> {
+        let present = self.display.interface_mut().probe();
+        match (self.present, present) {
+            (false, true) => self.restore()?,
+            (true, false) => {
+                #[cfg(feature = "log")]
+                log::warn!("LCD module disconnected");
+                self.present = false;
+            }
+            _ => {}
+        }
+        Ok(self.present)
+    }
+
+    // Brings a module which came back uninitialized to the recorded state. It stays missing if
+    // this fails, so the next poll tries again.
+    fn restore(&mut self) -> Result<(), I::Error> {
+        #[cfg(feature = "log")]
+        log::info!("LCD module reconnected, restoring its contents");
+        self.display.reinit()?;
+        // Upload and redraw left to right without shifting the display
+        let entry_mode = self.display.entry_mode();
+        self.display.send(Command::EntryModeSet(INCREMENT))?;
+        for location in 0..8 {
+            if self.uploaded & (1 << location) != 0 {
+                let glyph = self.glyphs[location as usize];
+                self.display.set_custom_char(location, &glyph)?;
+            }
+        }
+        let mut address = 0;
+        while address < DDRAM_SIZE {
+            if self.written & (1 << address) == 0 {
+                address += 1;
+                continue;
+            }
+            let start = address;
+            while address < DDRAM_SIZE && self.written & (1 << address) != 0 {
+                address += 1;
+            }
+            self.display.send(Command::SetDdramAddress(start as u8))?;
+            self.display.write_bytes(&self.ddram[start..address])?;
+        }
+        self.display.send(Command::EntryModeSet(entry_mode))?;
+        // Take the shorter way to the display shift
+        let shift = match self.scroll <= LINE_LENGTH / 2 {
+            true => Command::Shift(Shift::Display, ShiftDirection::Right),
+            false => Command::Shift(Shift::Display, ShiftDirection::Left),
+        };
+        let count = self.scroll.min(LINE_LENGTH - self.scroll);
+        self.display.send_all((0..count).map(|_| shift))?;
+        self.display.send(self.counter.command())?;
+        self.present = true;
+        self.restores = self.restores.wrapping_add(1);
+        Ok(())
+    }
+
+    // Records the effect of the command on the contents of the module
+    fn record(&mut self, command: Command) {
+        match command {
+            Command::Data(data) => match self.counter.cgram {
+                true => {
+                    let location = (self.counter.address >> 3) & 0b0000_0111;
+                    let row = self.counter.address & 0b0000_0111;
+                    self.glyphs[location as usize][row as usize] = data;
+                    self.uploaded |= 1 << location;
+                }
+                false => {
+                    let address = self.counter.address as usize % DDRAM_SIZE;
+                    self.ddram[address] = data;
+                    self.written |= 1 << address;
+                    // The display moves against the cursor, which keeps its place on the screen
+                    let entry_mode = self.display.entry_mode();
+                    if entry_mode & AUTOSHIFT != 0 {
+                        self.scroll(match entry_mode & INCREMENT != 0 {
+                            true => ShiftDirection::Left,
+                            false => ShiftDirection::Right,
+                        });
+                    }
+                }
+            },
+            Command::Clear => {
+                self.ddram = [b' '; DDRAM_SIZE];
+                self.written = 0;
+                self.scroll = 0;
+            }
+            Command::Home => self.scroll = 0,
+            Command::Shift(Shift::Display, direction) => self.scroll(direction),
+            // Taken over by the display even while the module is missing, so the restore
+            // brings them back
+            Command::EntryModeSet(_) | Command::DisplayControl(_) => {
+                self.display.follow_modes(command)
+            }
+            _ => {}
+        }
+        let layout = self.display.ddram_layout();
+        self.counter
+            .follow(command, self.display.entry_mode(), layout);
+    }
+
+    #[inline]
+    fn scroll(&mut self, direction: ShiftDirection) {
+        self.scroll = match direction {
+            ShiftDirection::Right => (self.scroll + 1) % LINE_LENGTH,
+            ShiftDirection::Left => (self.scroll + LINE_LENGTH - 1) % LINE_LENGTH,
+        };
+    }
+
+    // Passes the operation on while the module is present, marking it as missing on errors
+    fn forward<F>(&mut self, f: F) -> Result<(), I::Error>
+    where
+        F: FnOnce(&mut Display<I, W, Blocking, C, K>) -> Result<(), I::Error>,
+    {
+        if !self.present {
+            return Ok(());
+        }
+        let result = f(&mut self.display);
+        if result.is_err() {
+            self.present = false;
+        }
+        result
+    }
+
+    pub fn send(&mut self, command: Command) -> Result<(), I::Error> {
+        self.record(command);
+        self.forward(|display| display.send(command))
+    }
+
+    #[inline]
+    pub fn clear(&mut self) -> Result<(), I::Error> {
+        self.send(Command::Clear)
+    }
+
+    #[inline]
+    pub fn home(&mut self) -> Result<(), I::Error> {
+        self.send(Command::Home)
+    }
+
+    #[inline]
+    pub fn shift(&mut self, shift: Shift, shift_direction: ShiftDirection) -> Result<(), I::Error> {
+        self.send(Command::Shift(shift, shift_direction))
+    }
+
+    #[inline]
+    pub fn pos(&mut self, line: Lines, position: u8) -> Result<(), I::Error> {
+        let address = self.display.address(line, position);
+        self.send(Command::SetDdramAddress(address))
+    }
+
+    pub fn set_custom_char(&mut self, location: u8, bitmap: &[u8; 8]) -> Result<(), I::Error> {
+        self.record(Command::SetCgramAddress((location & 0b0000_0111) << 3));
+        bitmap.iter().for_each(|b| self.record(Command::Data(*b)));
+        self.forward(|display| display.set_custom_char(location, bitmap))
+    }
+
+    #[inline]
+    pub fn write_byte(&mut self, data: u8) -> Result<(), I::Error> {
+        self.send(Command::Data(data))
+    }
+
+    pub fn write_bytes(&mut self, data: &[u8]) -> Result<(), I::Error> {
+        data.iter().for_each(|b| self.record(Command::Data(*b)));
+        self.forward(|display| display.write_bytes(data))
+    }
+
+    pub fn write_string<S: AsRef<str>>(&mut self, s: S) -> Result<(), I::Error> {
+        s.as_ref()
+            .chars()
+            .for_each(|c| self.record(Command::Data(character_as_byte(c))));
+        self.forward(|display| display.write_string(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::EightBitBus;
+    use crate::mock::Recorder;
+
+    fn supervisor(shift: Shift) -> Supervisor<Recorder, EightBitBus> {
+        let display = Display::new(Recorder::default()).with_shift(shift, ShiftDirection::Right);
+        Supervisor::new(display)
+    }
+
+    // Unplugs the module, lets `f` write meanwhile and plugs it back in
+    fn reconnect<F>(supervisor: &mut Supervisor<Recorder, EightBitBus>, f: F) -> Recorder
+    where
+        F: FnOnce(&mut Supervisor<Recorder, EightBitBus>),
+    {
+        supervisor.display_mut().interface_mut().unplugged = true;
+        assert_eq!(supervisor.poll(), Ok(false));
+        f(supervisor);
+        *supervisor.display_mut().interface_mut() = Recorder::default();
+        assert_eq!(supervisor.poll(), Ok(true));
+        core::mem::take(supervisor.display_mut().interface_mut())
+    }
+
+    fn instruction(command: Command) -> u8 {
+        command.encode().unwrap().0
+    }
+
+    #[test]
+    fn restores_contents_and_cursor() {
+        let bitmap = [1, 2, 3, 4, 5, 6, 7, 8];
+        let mut supervisor = supervisor(Shift::Cursor);
+        supervisor.set_custom_char(1, &bitmap).unwrap();
+        supervisor.pos(Lines::_1, 0).unwrap();
+        supervisor.write_string("ab").unwrap();
+        supervisor.pos(Lines::_2, 3).unwrap();
+        supervisor.write_bytes(b"cd").unwrap();
+        let bus = reconnect(&mut supervisor, |supervisor| {
+            supervisor.write_byte(b'e').unwrap();
+        });
+        assert_eq!(supervisor.restores(), 1);
+        assert_eq!(bus.initializations, 1);
+        // The glyph, then both runs of written characters
+        assert_eq!(bus.data(), [&bitmap[..], b"ab", b"cde"].concat());
+        let instructions = bus.instructions();
+        assert!(instructions.contains(&instruction(Command::SetCgramAddress(1 << 3))));
+        assert!(instructions.ends_with(&[
+            instruction(Command::SetDdramAddress(0x00)),
+            instruction(Command::SetDdramAddress(0x43)),
+            instruction(Command::EntryModeSet(INCREMENT)),
+            instruction(Command::SetDdramAddress(0x46)),
+        ]));
+    }
+
+    #[test]
+    fn restores_run_across_the_line_wrap() {
+        let display = Display::new(Recorder::default())
+            .with_lines(Lines::_2)
+            .with_shift(Shift::Cursor, ShiftDirection::Right);
+        let mut supervisor = Supervisor::new(display);
+        supervisor.pos(Lines::_1, 38).unwrap();
+        supervisor.write_bytes(b"xyz").unwrap();
+        let bus = reconnect(&mut supervisor, |_| {});
+        // The counter jumps from the end of the first line to the start of the second one
+        assert_eq!(bus.data(), b"xyz");
+        assert!(bus.instructions().ends_with(&[
+            instruction(Command::SetDdramAddress(0x26)),
+            instruction(Command::SetDdramAddress(0x40)),
+            instruction(Command::EntryModeSet(INCREMENT)),
+            instruction(Command::SetDdramAddress(0x41)),
+        ]));
+    }
+
+    #[test]
+    fn restores_scroll_the_shorter_way() {
+        let left = instruction(Command::Shift(Shift::Display, ShiftDirection::Left));
+        let right = instruction(Command::Shift(Shift::Display, ShiftDirection::Right));
+        let mut supervisor = supervisor(Shift::Cursor);
+        (0..3).for_each(|_| {
+            supervisor
+                .shift(Shift::Display, ShiftDirection::Left)
+                .unwrap()
+        });
+        let bus = reconnect(&mut supervisor, |_| {});
+        let instructions = bus.instructions();
+        assert_eq!(instructions.iter().filter(|i| **i == left).count(), 3);
+        assert!(!instructions.contains(&right));
+
+        let bus = reconnect(&mut supervisor, |supervisor| {
+            (0..5).for_each(|_| {
+                supervisor
+                    .shift(Shift::Display, ShiftDirection::Right)
+                    .unwrap()
+            });
+        });
+        let instructions = bus.instructions();
+        assert_eq!(instructions.iter().filter(|i| **i == right).count(), 2);
+        assert!(!instructions.contains(&left));
+    }
+
+    #[test]
+    fn follows_autoshift() {
+        let left = instruction(Command::Shift(Shift::Display, ShiftDirection::Left));
+        let mut supervisor = supervisor(Shift::Display);
+        supervisor.write_string("abc").unwrap();
+        let bus = reconnect(&mut supervisor, |_| {});
+        let instructions = bus.instructions();
+        // Redrawn without shifting, then shifted by one position per character
+        assert_eq!(bus.data(), b"abc");
+        assert!(instructions.ends_with(&[
+            instruction(Command::EntryModeSet(INCREMENT | AUTOSHIFT)),
+            left,
+            left,
+            left,
+            instruction(Command::SetDdramAddress(0x03)),
+        ]));
+    }
+
+    #[test]
+    fn restores_the_entry_mode_set_by_send() {
+        let left = instruction(Command::Shift(Shift::Display, ShiftDirection::Left));
+        let autoshift = instruction(Command::EntryModeSet(INCREMENT | AUTOSHIFT));
+        let mut supervisor = supervisor(Shift::Cursor);
+        supervisor
+            .send(Command::EntryModeSet(INCREMENT | AUTOSHIFT))
+            .unwrap();
+        supervisor.write_string("ab").unwrap();
+        let bus = reconnect(&mut supervisor, |supervisor| {
+            supervisor.write_byte(b'c').unwrap();
+        });
+        let instructions = bus.instructions();
+        // Initialized and restored with the autoshift, one display shift per character
+        assert_eq!(instructions.iter().filter(|i| **i == autoshift).count(), 2);
+        assert!(instructions.ends_with(&[
+            autoshift,
+            left,
+            left,
+            left,
+            instruction(Command::SetDdramAddress(0x03)),
+        ]));
+    }
+}
@@ -0,0 +1,84 @@
+/// Timing of the bus signals and the execution times of the controller.
+///
+/// The presets cover the common controllers. For modules needing longer pulses, e.g. 5V
+/// controllers run at 3.3V, start from a preset and adjust the individual fields.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Timing {
+    /// Time for the supply voltage to settle before the initialization in µs
+    pub power_on_us: u32,
+    /// RS setup time before the rising edge of E in ns
+    pub setup_ns: u32,
+    /// Width of the E pulse in ns
+    pub enable_pulse_ns: u32,
+    /// Time after the falling edge of E until the next pulse in ns
+    pub enable_hold_ns: u32,
+    /// Waits of the initialization by instruction after the first and second function set in µs
+    pub init_long_us: u32,
+    pub init_short_us: u32,
+    /// Execution time of most instructions in µs
+    pub execution_us: u32,
+    /// Execution time of the clear display and return home instructions in µs
+    pub clear_us: u32,
+    pub home_us: u32,
+}
+
+impl Timing {
+    /// HD44780U datasheet values for a supply voltage of 2.7 - 4.5V
+    pub const HD44780: Self = Self {
+        power_on_us: 40_000,
+        setup_ns: 60,
+        enable_pulse_ns: 450,
+        enable_hold_ns: 550,
+        init_long_us: 4100,
+        init_short_us: 100,
+        execution_us: 37,
+        clear_us: 1520,
+        home_us: 1520,
+    };
+
+    /// ST7066U datasheet values at 3V, which are slower than those of the HD44780
+    pub const ST7066U: Self = Self {
+        power_on_us: 40_000,
+        setup_ns: 100,
+        enable_pulse_ns: 480,
+        enable_hold_ns: 720,
+        init_long_us: 4100,
+        init_short_us: 100,
+        execution_us: 37,
+        clear_us: 1520,
+        home_us: 1520,
+    };
+
+    /// Generous margins for clones with a slow oscillator
+    pub const SLOW: Self = Self {
+        power_on_us: 100_000,
+        setup_ns: 200,
+        enable_pulse_ns: 1000,
+        enable_hold_ns: 1000,
+        init_long_us: 5000,
+        init_short_us: 200,
+        execution_us: 80,
+        clear_us: 3000,
+        home_us: 3000,
+    };
+
+    /// No waits at all, for emulators and simulated displays
+    pub const FAST: Self = Self {
+        power_on_us: 0,
+        setup_ns: 0,
+        enable_pulse_ns: 0,
+        enable_hold_ns: 0,
+        init_long_us: 0,
+        init_short_us: 0,
+        execution_us: 0,
+        clear_us: 0,
+        home_us: 0,
+    };
+}
+
+impl Default for Timing {
+    #[inline]
+    fn default() -> Self {
+        Self::HD44780
+    }
+}